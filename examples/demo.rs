@@ -0,0 +1,100 @@
+use bevy::prelude::*;
+use bevy_tests::{CameraCommand, CameraRigBundle, FourXCameraPlugin};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(FourXCameraPlugin::default())
+        .add_startup_system(setup)
+        .add_system(pan_to_cube_button)
+        .run();
+}
+
+/// set up a simple 3D scene
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // For an orthographic camera spawn `CameraRigBundle::orthographic` instead,
+    // see the pixel_art example for pixel art
+    commands
+        // camera
+        .spawn(CameraRigBundle::default())
+        .with_children(|cb| {
+            cb.spawn(Camera3dBundle {
+                // I recommend setting the fov to a low value to get a
+                // a pseudo-orthographic perspective
+                projection: PerspectiveProjection {
+                    fov: 0.1,
+                    ..Default::default()
+                }
+                .into(),
+                transform: Transform::from_translation(Vec3::new(-75.0, 75., 0.0))
+                    .looking_at(Vec3::ZERO, Vec3::Y),
+                ..Default::default()
+            });
+        });
+
+    // plane
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(shape::Plane::from_size(5.0).into()),
+        material: materials.add(Color::rgb(0.3, 0.5, 0.3).into()),
+        ..default()
+    });
+    // cubes
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
+        material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+        transform: Transform::from_xyz(1.5, 0.5, 1.5),
+        ..default()
+    });
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
+        material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+        transform: Transform::from_xyz(1.5, 0.5, -1.5),
+        ..default()
+    });
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
+        material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+        transform: Transform::from_xyz(-1.5, 0.5, 1.5),
+        ..default()
+    });
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
+        material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+        transform: Transform::from_xyz(-1.5, 0.5, -1.5),
+        ..default()
+    });
+    // light
+    commands.spawn(PointLightBundle {
+        transform: Transform::from_xyz(3.0, 8.0, 5.0),
+        ..default()
+    });
+    // pans to a cube when clicked
+    commands.spawn(ButtonBundle {
+        style: Style {
+            size: Size::new(Val::Px(40.0), Val::Px(40.0)),
+            margin: UiRect::all(Val::Px(10.0)),
+            ..default()
+        },
+        background_color: Color::rgb(0.8, 0.7, 0.6).into(),
+        ..default()
+    });
+}
+
+fn pan_to_cube_button(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<Button>)>,
+    mut camera_commands: EventWriter<CameraCommand>,
+) {
+    for interaction in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            camera_commands.send(CameraCommand::PanTo {
+                rig: None,
+                target: Vec3::new(1.5, 0.0, 1.5),
+                snap: false,
+            });
+        }
+    }
+}
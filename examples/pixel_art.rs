@@ -0,0 +1,92 @@
+//! A 2D camera looking down on pixel art, kept on the pixel grid with
+//! `CameraRig::pixel_snap`. Pan with WASD and press F to follow the walker.
+
+use bevy::prelude::*;
+use bevy_tests::{CameraRigBundle, CameraRigFollow, FourXCameraPlugin};
+
+/// Screen pixels to each pixel of the art
+const ZOOM: f32 = 4.;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(FourXCameraPlugin::default())
+        .add_startup_system(setup)
+        .add_system(walk)
+        .run();
+}
+
+#[derive(Component)]
+struct Walker;
+
+/// Lays a sprite flat on the ground, its top towards the top of the screen
+fn on_ground(x: f32, z: f32) -> Transform {
+    Transform::from_xyz(x, 0., z).looking_to(Vec3::NEG_Y, Vec3::X)
+}
+
+fn setup(mut commands: Commands) {
+    let mut rig = CameraRigBundle::default();
+    // One world unit to each pixel of the art
+    rig.camera_rig.pixel_snap = Some(1.);
+    // Pan slower than a pixel an update, which still gets there
+    rig.camera_rig.keyboard = rig
+        .camera_rig
+        .keyboard
+        .with_zoom_coupled_sensitivity(false)
+        .with_move_sensitivity(0., 0.4);
+    // Keep the camera looking straight down
+    rig.camera_rig.mouse.rotate = Vec::new();
+    rig.camera_rig.mouse.zoom_sensitivity = 0.;
+
+    commands.spawn(rig).with_children(|rig| {
+        let mut camera = Camera2dBundle::default();
+        camera.projection.scale = 1. / ZOOM;
+        camera.transform = Transform::from_xyz(0., camera.transform.translation.z, 0.)
+            .looking_to(Vec3::NEG_Y, Vec3::X);
+        rig.spawn(camera);
+    });
+
+    // A checkerboard of 16 pixel tiles
+    for x in -8..8 {
+        for z in -8..8 {
+            let color = if (x + z) % 2 == 0 {
+                Color::rgb(0.3, 0.5, 0.3)
+            } else {
+                Color::rgb(0.25, 0.45, 0.25)
+            };
+            commands.spawn(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(16.)),
+                    ..default()
+                },
+                transform: on_ground(x as f32 * 16., z as f32 * 16.),
+                ..default()
+            });
+        }
+    }
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgb(0.8, 0.7, 0.6),
+                custom_size: Some(Vec2::new(6., 10.)),
+                ..default()
+            },
+            // Above the tiles, so it is drawn over them
+            transform: on_ground(0., 0.).with_translation(Vec3::new(0., 1., 0.)),
+            ..default()
+        },
+        Walker,
+        CameraRigFollow::new(false),
+    ));
+}
+
+/// Walks in a slow circle, so following shows the rig staying on the grid
+fn walk(time: Res<Time>, mut walkers: Query<&mut Transform, With<Walker>>) {
+    let angle = time.elapsed_seconds() * 0.2;
+    for mut transform in walkers.iter_mut() {
+        transform.translation.x = angle.cos() * 40.;
+        transform.translation.z = angle.sin() * 40.;
+    }
+}
@@ -0,0 +1,80 @@
+//! Shows how to change a rig's controls while the game is running.
+//! Press R to swap panning between WASD and the arrow keys.
+
+use bevy::prelude::*;
+use bevy_tests::{CameraRig, CameraRigBundle, FourXCameraPlugin};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(FourXCameraPlugin::default())
+        .add_startup_system(setup)
+        .add_system(rebind_controls)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut rig = CameraRigBundle::default();
+    rig.camera_rig.keyboard.forward = vec![KeyCode::W];
+    rig.camera_rig.keyboard.backward = vec![KeyCode::S];
+    rig.camera_rig.keyboard.left = vec![KeyCode::A];
+    rig.camera_rig.keyboard.right = vec![KeyCode::D];
+
+    commands.spawn(rig).with_children(|cb| {
+        cb.spawn(Camera3dBundle {
+            projection: PerspectiveProjection {
+                fov: 0.1,
+                ..Default::default()
+            }
+            .into(),
+            transform: Transform::from_translation(Vec3::new(-75.0, 75., 0.0))
+                .looking_at(Vec3::ZERO, Vec3::Y),
+            ..Default::default()
+        });
+    });
+
+    // plane
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(shape::Plane::from_size(5.0).into()),
+        material: materials.add(Color::rgb(0.3, 0.5, 0.3).into()),
+        ..default()
+    });
+    // cube
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
+        material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+        transform: Transform::from_xyz(0.0, 0.5, 0.0),
+        ..default()
+    });
+    // light
+    commands.spawn(PointLightBundle {
+        transform: Transform::from_xyz(3.0, 8.0, 5.0),
+        ..default()
+    });
+}
+
+fn rebind_controls(keyboard_input: Res<Input<KeyCode>>, mut rig_query: Query<&mut CameraRig>) {
+    if !keyboard_input.just_pressed(KeyCode::R) {
+        return;
+    }
+    for mut rig in rig_query.iter_mut() {
+        let keyboard = &mut rig.keyboard;
+        if keyboard.forward.contains(&KeyCode::W) {
+            keyboard.forward = vec![KeyCode::Up];
+            keyboard.backward = vec![KeyCode::Down];
+            keyboard.left = vec![KeyCode::Left];
+            keyboard.right = vec![KeyCode::Right];
+            info!("Panning with the arrow keys");
+        } else {
+            keyboard.forward = vec![KeyCode::W];
+            keyboard.backward = vec![KeyCode::S];
+            keyboard.left = vec![KeyCode::A];
+            keyboard.right = vec![KeyCode::D];
+            info!("Panning with WASD");
+        }
+    }
+}
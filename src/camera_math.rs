@@ -0,0 +1,173 @@
+//! Plain math behind the rig systems, taking and returning glam values so it
+//! can be checked without an `App`. Camera offsets are the camera's transform
+//! relative to its rig
+
+use bevy::prelude::*;
+
+/// Moves `target` by `input` scaled by `sensitivity`, with `input.y` along
+/// the first axis of `basis` (forward) and `input.x` along the second (right)
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tests::camera_math::apply_pan;
+/// let basis = (Vec3::X, Vec3::Z);
+/// let moved = apply_pan(Vec3::ONE, basis, Vec2::new(1., 2.), 0.5);
+/// assert_eq!(moved, Vec3::new(2., 1., 1.5));
+///
+/// // No input or sensitivity stays put
+/// assert_eq!(apply_pan(Vec3::ONE, basis, Vec2::ZERO, 1.), Vec3::ONE);
+/// assert_eq!(apply_pan(Vec3::ONE, basis, Vec2::ONE, 0.), Vec3::ONE);
+/// ```
+pub fn apply_pan(target: Vec3, basis: (Vec3, Vec3), input: Vec2, sensitivity: f32) -> Vec3 {
+    target + (basis.0 * input.y + basis.1 * input.x) * sensitivity
+}
+
+/// Turns a camera offset around the rig by `yaw_delta` around `Vec3::Y`, then
+/// raises it by `pitch_delta`, keeping it the same distance away and facing
+/// the same way relative to the rig. With `pitch_limits` the pitch is then
+/// kept within (min, max) radians above the ground. A zero length offset, or
+/// one straight above or below the rig, can't be pitched
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tests::camera_math::{camera_pitch, orbit};
+/// # use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+/// let camera = Transform::from_xyz(-10., 10., 0.).looking_at(Vec3::ZERO, Vec3::Y);
+///
+/// let raised = orbit(camera, 0., 0.2, None);
+/// assert!((camera_pitch(raised.translation) - (FRAC_PI_4 + 0.2)).abs() < 0.0001);
+/// assert!((raised.translation.length() - camera.translation.length()).abs() < 0.0001);
+/// // Still looking at the rig
+/// assert!(raised.forward().dot(-raised.translation.normalize()) > 0.9999);
+///
+/// let turned = orbit(camera, FRAC_PI_2, 0., None);
+/// assert!(turned.translation.abs_diff_eq(Vec3::new(0., 10., 10.), 0.0001));
+/// assert!(turned.forward().dot(-turned.translation.normalize()) > 0.9999);
+///
+/// // Stops at the limits, including when already on one
+/// let limits = Some((0.5, 1.));
+/// assert!((camera_pitch(orbit(camera, 0., 1., limits).translation) - 1.).abs() < 0.0001);
+/// assert!((camera_pitch(orbit(camera, 0., -1., limits).translation) - 0.5).abs() < 0.0001);
+/// let at_max = orbit(camera, 0., 1., limits);
+/// assert!((camera_pitch(orbit(at_max, 0., 0.1, limits).translation) - 1.).abs() < 0.0001);
+///
+/// // Nothing to pitch around
+/// assert_eq!(orbit(Transform::IDENTITY, 0., 0.5, limits), Transform::IDENTITY);
+/// let above = Transform::from_xyz(0., 10., 0.);
+/// assert_eq!(orbit(above, 0., 0.5, None), above);
+/// ```
+pub fn orbit(
+    offset: Transform,
+    yaw_delta: f32,
+    pitch_delta: f32,
+    pitch_limits: Option<(f32, f32)>,
+) -> Transform {
+    let mut offset = offset;
+    if yaw_delta != 0. {
+        let yaw = Quat::from_rotation_y(yaw_delta);
+        offset.translation = yaw * offset.translation;
+        offset.rotation = yaw * offset.rotation;
+    }
+    if pitch_delta != 0. {
+        pitch_camera(&mut offset, pitch_delta);
+    }
+    if let Some((min, max)) = pitch_limits {
+        let current = camera_pitch(offset.translation);
+        let clamped = current.clamp(min, max);
+        if clamped != current {
+            pitch_camera(&mut offset, clamped - current);
+        }
+    }
+    offset
+}
+
+/// Moves a camera offset towards the rig by `wheel * sensitivity` of its
+/// length, or away for a negative `wheel`, never changing its direction. With
+/// `limits` the length is kept within (min, max), and zooming through the rig
+/// stops at the min rather than coming out the other side. A zero min never
+/// puts the camera on the rig, it stays where it was instead, as it does
+/// without `limits` for a zoom that would reach or pass through the rig
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tests::camera_math::dolly;
+/// let offset = Vec3::new(-10., 10., 0.);
+/// assert_eq!(dolly(offset, 1., 0.1, None), offset * 0.9);
+/// assert_eq!(dolly(offset, -1., 0.1, None), offset * 1.1);
+/// assert_eq!(dolly(offset, 1., 1., None), offset);
+/// assert_eq!(dolly(offset, 2., 1., None), offset);
+///
+/// let limits = Some((5., 20.));
+/// let length = |offset: Vec3| offset.length();
+/// assert!((length(dolly(offset, 1., 0.9, limits)) - 5.).abs() < 0.0001);
+/// assert!((length(dolly(offset, -10., 0.1, limits)) - 20.).abs() < 0.0001);
+/// // Overshooting the rig
+/// let through = dolly(offset, 1., 2., limits);
+/// assert!((length(through) - 5.).abs() < 0.0001);
+/// assert!(through.dot(offset) > 0.);
+///
+/// // A zero length offset has no direction to move in
+/// assert_eq!(dolly(Vec3::ZERO, 1., 0.1, None), Vec3::ZERO);
+/// assert_eq!(dolly(Vec3::ZERO, 1., 0.1, limits), Vec3::ZERO);
+/// assert_eq!(dolly(offset, 1., 2., Some((0., 20.))), offset);
+/// ```
+pub fn dolly(offset: Vec3, wheel: f32, sensitivity: f32, limits: Option<(f32, f32)>) -> Vec3 {
+    let moved = offset - offset * wheel * sensitivity;
+    let (min, max) = if let Some(limits) = limits {
+        limits
+    } else if moved.dot(offset) <= 0. {
+        return offset;
+    } else {
+        return moved;
+    };
+    let mut distance = moved.length();
+    if moved.dot(offset) <= 0. {
+        distance = -distance;
+    }
+    let clamped = offset.normalize_or_zero() * distance.clamp(min, max);
+    if clamped == Vec3::ZERO {
+        offset
+    } else {
+        clamped
+    }
+}
+
+/// Moves `current` towards `target` by `dt / time_constant` of the gap
+/// between them, arriving once `dt` reaches `time_constant`. A zero
+/// `time_constant` jumps straight there and a zero `dt` stays put
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tests::camera_math::smooth_toward;
+/// let target = Vec3::new(10., 0., 0.);
+/// assert_eq!(smooth_toward(Vec3::ZERO, target, 0.05, 0.1), Vec3::new(5., 0., 0.));
+/// assert_eq!(smooth_toward(Vec3::ZERO, target, 0., 0.1), Vec3::ZERO);
+/// assert_eq!(smooth_toward(Vec3::ZERO, target, 0.05, 0.), target);
+/// // Long frames don't overshoot
+/// assert_eq!(smooth_toward(Vec3::ZERO, target, 1., 0.1), target);
+/// ```
+pub fn smooth_toward(current: Vec3, target: Vec3, dt: f32, time_constant: f32) -> Vec3 {
+    if time_constant <= 0. {
+        return target;
+    }
+    current.lerp(target, (dt / time_constant).clamp(0., 1.))
+}
+
+/// Angle in radians of a camera offset above the ground plane through the rig
+pub fn camera_pitch(offset: Vec3) -> f32 {
+    (offset.y / offset.length().max(f32::EPSILON))
+        .clamp(-1., 1.)
+        .asin()
+}
+
+/// Rotates a camera offset up around the rig by `angle`, keeping its distance
+/// and heading
+pub(crate) fn pitch_camera(camera: &mut Transform, angle: f32) {
+    let axis = camera.translation.cross(Vec3::Y);
+    if axis.length_squared() <= f32::EPSILON {
+        return;
+    }
+    let rotation = Quat::from_axis_angle(axis.normalize(), angle);
+    camera.translation = rotation * camera.translation;
+    camera.rotation = rotation * camera.rotation;
+}
@@ -0,0 +1,98 @@
+use bevy::{
+    prelude::*,
+    render::camera::{CameraProjection, Projection},
+};
+
+/// Ray from the camera through `screen_pos`, which has its origin at the
+/// bottom left of `window` in logical pixels, matching
+/// `Window::cursor_position`. The ray starts on the near plane and points away
+/// from the camera
+pub fn screen_to_world_ray(
+    screen_pos: Vec2,
+    window: &Window,
+    cam_transform: &GlobalTransform,
+    projection: &Projection,
+) -> Ray {
+    let size = Vec2::new(window.width(), window.height());
+    ndc_to_world_ray(
+        screen_pos / size * 2. - Vec2::ONE,
+        cam_transform,
+        projection,
+    )
+}
+
+/// Ray from the camera through `ndc`, normalized device coordinates from -1
+/// at the bottom left of the view to 1 at the top right
+pub(crate) fn ndc_to_world_ray(
+    ndc: Vec2,
+    cam_transform: &GlobalTransform,
+    projection: &Projection,
+) -> Ray {
+    // View space to world space, undoing the projection first
+    let ndc_to_world =
+        cam_transform.compute_matrix() * projection.get_projection_matrix().inverse();
+    // Bevy uses reversed z, so the near plane is at 1 and far is towards 0
+    let near = ndc_to_world.project_point3(ndc.extend(1.));
+
+    let direction = match projection {
+        // Every ray is parallel to the view direction
+        Projection::Orthographic(_) => cam_transform.forward(),
+        // Rays spread out from the camera, the perspective projection is
+        // infinite so unproject a point just short of the far plane
+        Projection::Perspective(_) => {
+            let far = ndc_to_world.project_point3(ndc.extend(f32::EPSILON));
+            (far - near).normalize()
+        }
+    };
+
+    Ray {
+        origin: near,
+        direction,
+    }
+}
+
+/// Normalized device coordinates of `point`, `None` when it is behind the
+/// camera
+pub(crate) fn world_to_ndc(
+    point: Vec3,
+    cam_transform: &GlobalTransform,
+    projection: &Projection,
+) -> Option<Vec3> {
+    let world_to_clip =
+        projection.get_projection_matrix() * cam_transform.compute_matrix().inverse();
+    let clip = world_to_clip * point.extend(1.);
+    (clip.w > 0.).then(|| clip.truncate() / clip.w)
+}
+
+/// Area of the ground at `height` the camera shows, as the smallest rectangle
+/// in the XZ plane around it, with x and y of the `Vec2`s mapping to world x
+/// and z. Near the horizon the corners of the view reach the ground a long
+/// way off or not at all, so they are cut off at the far plane
+pub fn ground_footprint(
+    cam_transform: &GlobalTransform,
+    projection: &Projection,
+    height: f32,
+) -> Rect {
+    let far = projection.far();
+    let corners = [
+        Vec2::new(-1., -1.),
+        Vec2::new(1., -1.),
+        Vec2::new(1., 1.),
+        Vec2::new(-1., 1.),
+    ]
+    .map(|ndc| {
+        let ray = ndc_to_world_ray(ndc, cam_transform, projection);
+        let distance = if ray.direction.y < 0. {
+            ((height - ray.origin.y) / ray.direction.y).clamp(0., far)
+        } else {
+            far
+        };
+        let point = ray.origin + ray.direction * distance;
+        Vec2::new(point.x, point.z)
+    });
+    let mut footprint = Rect::from_corners(corners[0], corners[1]);
+    for corner in &corners[2..] {
+        footprint = footprint.union_point(*corner);
+    }
+    footprint
+}
@@ -0,0 +1,67 @@
+//! Helpers for games built around the rig, like placing buildings on a grid
+//! under the cursor
+
+use bevy::prelude::*;
+
+/// Grid on the ground with points every `cell_size` along x and z, shifted
+/// by `offset`
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnapGrid {
+    /// Spacing along x and z, a zero spacing leaves that axis unsnapped
+    pub cell_size: Vec2,
+    /// A grid point, as x and z
+    pub offset: Vec2,
+}
+
+impl Default for SnapGrid {
+    fn default() -> Self {
+        SnapGrid {
+            cell_size: Vec2::ONE,
+            offset: Vec2::ZERO,
+        }
+    }
+}
+
+/// Grid point nearest to `world_pos` across the ground, at its height. For
+/// drawing build indicators under [`CursorGroundPosition`](crate::CursorGroundPosition)
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tests::camera_rig_utils::{nearest_grid_point, SnapGrid};
+/// let grid = SnapGrid {
+///     cell_size: Vec2::new(2., 5.),
+///     ..Default::default()
+/// };
+/// assert_eq!(nearest_grid_point(Vec3::new(2.9, 3., 6.), &grid), Vec3::new(2., 3., 5.));
+/// assert_eq!(nearest_grid_point(Vec3::new(-3.2, 0., -7.6), &grid), Vec3::new(-4., 0., -10.));
+///
+/// // Points are on the offset rather than the origin
+/// let offset = SnapGrid {
+///     offset: Vec2::new(0.5, 1.),
+///     ..grid
+/// };
+/// assert_eq!(nearest_grid_point(Vec3::new(0., 0., 0.), &offset), Vec3::new(0.5, 0., 1.));
+/// assert_eq!(nearest_grid_point(Vec3::new(-1.2, 0., -2.), &offset), Vec3::new(-1.5, 0., -4.));
+///
+/// // A zero spacing doesn't snap that axis
+/// let rows = SnapGrid {
+///     cell_size: Vec2::new(0., 5.),
+///     ..Default::default()
+/// };
+/// assert_eq!(nearest_grid_point(Vec3::new(1.3, 0., 2.), &rows), Vec3::new(1.3, 0., 0.));
+/// ```
+pub fn nearest_grid_point(world_pos: Vec3, grid: &SnapGrid) -> Vec3 {
+    let snap = |position: f32, cell_size: f32, offset: f32| {
+        if cell_size == 0. {
+            position
+        } else {
+            ((position - offset) / cell_size).round() * cell_size + offset
+        }
+    };
+    Vec3::new(
+        snap(world_pos.x, grid.cell_size.x, grid.offset.x),
+        world_pos.y,
+        snap(world_pos.z, grid.cell_size.y, grid.offset.y),
+    )
+}
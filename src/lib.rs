@@ -0,0 +1,5964 @@
+use bevy::{
+    ecs::system::{Command, SystemParam},
+    input::mouse::{MouseMotion, MouseWheel},
+    prelude::*,
+    render::{
+        camera::{Camera, ScalingMode},
+        view::RenderLayers,
+    },
+    transform::TransformSystem,
+    utils::{Duration, HashMap, HashSet},
+    window::{CursorGrabMode, CursorLeft, CursorMoved, PrimaryWindow, WindowFocused},
+};
+
+#[cfg(feature = "inspector")]
+use bevy_inspector_egui::prelude::*;
+use std::marker::PhantomData;
+
+pub mod camera_math;
+mod camera_rig_raycast;
+pub mod camera_rig_utils;
+#[cfg(test)]
+mod tests;
+
+use camera_math::{apply_pan, camera_pitch, dolly, orbit, pitch_camera, smooth_toward};
+pub use camera_rig_raycast::{ground_footprint, screen_to_world_ray};
+use camera_rig_raycast::{ndc_to_world_ray, world_to_ndc};
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
+#[system_set(base)]
+pub enum CameraSystem {
+    CameraRigMovement,
+    /// Runs after `CoreSet::Update`, so rigs don't trail a frame behind
+    /// entities moved there
+    CameraRigFollow,
+}
+
+/// Systems that raycast from the rig cameras, such as [`CursorGroundPosition`]
+/// and [`CameraGroundFocus`]. They run in `CoreSet::PostUpdate` after
+/// transform propagation, as the cameras' `GlobalTransform`s are stale until
+/// then. Order your own raycasts after this set to see this frame's camera
+/// and use its results
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
+pub struct CameraRaycastSet;
+
+#[derive(Default)]
+pub struct FourXCameraPlugin {
+    /// Give rigs spawned without a camera child a [`RigCamera`]
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::FourXCameraPlugin;
+    /// App::new().add_plugin(FourXCameraPlugin {
+    ///     auto_attach_camera: true,
+    /// });
+    /// ```
+    pub auto_attach_camera: bool,
+}
+
+impl Plugin for FourXCameraPlugin {
+    fn build(&self, app: &mut App) {
+        if self.auto_attach_camera {
+            app.add_system(camera_rig_auto_camera.in_base_set(CoreSet::PreUpdate));
+        }
+        app.init_resource::<GroundPlane>()
+            .init_resource::<CameraInputContexts>()
+            .init_resource::<CursorGroundPosition>()
+            .init_resource::<ActiveCameraRig>()
+            .init_resource::<SelectedEntity>()
+            .init_resource::<CameraRigSpeedMultiplier>()
+            .init_resource::<CameraBlend>()
+            .add_event::<CameraCommand>()
+            .add_event::<ZoomLevelChanged>()
+            .add_event::<FocusOnEntityCompleted>()
+            .add_event::<CameraSettingsChanged>()
+            .add_event::<CameraBlendFinished>()
+            .configure_set(
+                CameraSystem::CameraRigFollow
+                    .after(CoreSet::UpdateFlush)
+                    .before(CoreSet::PostUpdate),
+            )
+            .configure_set(
+                CameraRaycastSet
+                    .in_base_set(CoreSet::PostUpdate)
+                    .after(TransformSystem::TransformPropagate),
+            )
+            .add_system(
+                camera_follow_keys
+                    .in_base_set(CameraSystem::CameraRigMovement)
+                    .before(camera_commands),
+            )
+            .add_system(
+                camera_gamepad_buttons
+                    .in_base_set(CameraSystem::CameraRigMovement)
+                    .before(camera_commands),
+            )
+            .add_system(
+                camera_commands
+                    .in_base_set(CameraSystem::CameraRigMovement)
+                    .before(camera_rig_movement),
+            )
+            .add_system(
+                camera_focus
+                    .in_base_set(CameraSystem::CameraRigMovement)
+                    .after(camera_commands)
+                    .before(camera_rig_movement),
+            )
+            .add_system(
+                reset_camera_speed_multiplier_system
+                    .in_base_set(CameraSystem::CameraRigMovement)
+                    .before(camera_rig_movement),
+            )
+            .add_system(
+                camera_path_playback
+                    .in_base_set(CameraSystem::CameraRigMovement)
+                    .after(camera_focus)
+                    .before(camera_rig_movement),
+            )
+            .add_system(
+                camera_settings_changed
+                    .in_base_set(CameraSystem::CameraRigMovement)
+                    .before(camera_rig_movement),
+            )
+            .add_system(
+                camera_rig_warmup
+                    .in_base_set(CameraSystem::CameraRigMovement)
+                    .after(camera_commands)
+                    .before(camera_rig_movement),
+            )
+            .add_system(
+                camera_shake_reset
+                    .in_base_set(CameraSystem::CameraRigMovement)
+                    .before(camera_rig_movement),
+            )
+            .add_system(
+                camera_blend_reset
+                    .in_base_set(CameraSystem::CameraRigMovement)
+                    .after(camera_shake_reset)
+                    .before(camera_rig_movement),
+            )
+            .add_system(
+                camera_pixel_snap_reset
+                    .in_base_set(CameraSystem::CameraRigMovement)
+                    .after(camera_blend_reset)
+                    .before(camera_gamepad_buttons)
+                    .before(camera_commands),
+            )
+            .add_system(camera_rig_movement.in_base_set(CameraSystem::CameraRigMovement))
+            .add_system(
+                camera_fov_zoom
+                    .in_base_set(CameraSystem::CameraRigMovement)
+                    .after(camera_rig_movement),
+            )
+            .add_system(
+                camera_ortho_zoom
+                    .in_base_set(CameraSystem::CameraRigMovement)
+                    .after(camera_rig_movement),
+            )
+            .add_system(camera_cursor_grab.in_base_set(CameraSystem::CameraRigMovement))
+            .add_system(
+                camera_zoom_level
+                    .in_base_set(CameraSystem::CameraRigMovement)
+                    .after(camera_rig_movement),
+            )
+            .add_system(
+                camera_keep_in_view
+                    .in_base_set(CameraSystem::CameraRigMovement)
+                    .after(camera_rig_movement),
+            )
+            .add_system(
+                camera_rig_follow
+                    .in_base_set(CameraSystem::CameraRigFollow)
+                    .after(CameraSystem::CameraRigMovement),
+            )
+            .add_system(
+                camera_position_lock
+                    .in_base_set(CameraSystem::CameraRigFollow)
+                    .after(camera_rig_follow)
+                    .before(camera_pixel_snap)
+                    .before(camera_blend)
+                    .before(camera_shake)
+                    .before(camera_path_record),
+            )
+            .add_system(
+                camera_pixel_snap
+                    .in_base_set(CameraSystem::CameraRigFollow)
+                    .after(camera_rig_follow)
+                    .after(camera_path_record)
+                    .before(camera_blend),
+            )
+            .add_system(
+                camera_blend
+                    .in_base_set(CameraSystem::CameraRigFollow)
+                    .after(camera_rig_follow)
+                    .before(camera_shake),
+            )
+            .add_system(
+                camera_shake
+                    .in_base_set(CameraSystem::CameraRigFollow)
+                    .after(camera_rig_follow),
+            )
+            .add_system(
+                camera_path_record
+                    .in_base_set(CameraSystem::CameraRigFollow)
+                    .after(camera_rig_follow),
+            )
+            .add_system(
+                camera_mouse_conf_warning
+                    .in_base_set(CameraSystem::CameraRigMovement)
+                    .before(camera_rig_movement),
+            )
+            .add_system(
+                camera_scale_warning
+                    .in_base_set(CoreSet::PostUpdate)
+                    .after(TransformSystem::TransformPropagate),
+            )
+            .add_system(cursor_ground_position.in_set(CameraRaycastSet))
+            .add_system(camera_ground_focus.in_set(CameraRaycastSet))
+            .add_system(camera_cursor_system.in_set(CameraRaycastSet));
+
+        #[cfg(feature = "inspector")]
+        app.register_type::<CameraRig>()
+            .register_type::<KeyboardConf>()
+            .register_type::<MouseConf>()
+            .register_type::<GamepadConf>()
+            .register_type::<CameraRigFollow>();
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "inspector",
+    derive(Reflect, FromReflect, InspectorOptions),
+    reflect(InspectorOptions)
+)]
+pub struct KeyboardConf {
+    pub forward: Vec<KeyCode>,
+    pub backward: Vec<KeyCode>,
+    pub left: Vec<KeyCode>,
+    pub right: Vec<KeyCode>,
+    /// Whether the `forward` and `backward` keys move the rig, or pitch it
+    /// in [`CameraMode::Orbit`]. Turning this and the other `allow_*` flags
+    /// off keeps the bindings, so they can be turned back on at runtime.
+    /// [`CameraMode::FreeFly`] ignores them
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::{compute_rig_delta, CameraRig, CameraRigInput, CameraRigPose};
+    /// # use bevy_tests::KeyboardConf;
+    /// let mut keys = Input::default();
+    /// keys.press(KeyCode::W);
+    /// keys.press(KeyCode::D);
+    /// keys.press(KeyCode::Q);
+    /// let moved = |keyboard: KeyboardConf| {
+    ///     let rig = CameraRig {
+    ///         keyboard,
+    ///         ..Default::default()
+    ///     };
+    ///     let buttons = Input::default();
+    ///     let input = CameraRigInput::new(&rig.keyboard, &rig.mouse, &keys, &buttons);
+    ///     compute_rig_delta(&rig, &CameraRigPose::default(), &input).0
+    /// };
+    ///
+    /// let everything = moved(KeyboardConf::default());
+    /// assert!(everything.translation.x > 0.);
+    /// assert!(everything.translation.z > 0.);
+    /// assert_ne!(everything.rotation, Quat::IDENTITY);
+    ///
+    /// let pan_only = moved(
+    ///     KeyboardConf::default()
+    ///         .with_allow_rotate(false)
+    ///         .with_allow_strafe(false),
+    /// );
+    /// assert!(pan_only.translation.x > 0.);
+    /// assert_eq!(pan_only.translation.z, 0.);
+    /// assert_eq!(pan_only.rotation, Quat::IDENTITY);
+    ///
+    /// let strafe_only = moved(KeyboardConf::default().with_allow_pan(false));
+    /// assert_eq!(strafe_only.translation.x, 0.);
+    /// assert!(strafe_only.translation.z > 0.);
+    /// ```
+    pub allow_pan: bool,
+    /// Whether the `left` and `right` keys move the rig
+    pub allow_strafe: bool,
+    /// Whether the `clockwise` and `counter_clockwise` keys turn the rig, as
+    /// do the `left` and `right` keys in [`CameraMode::Orbit`]. In
+    /// [`CameraMode::FreeFly`] it is the `clockwise` and `counter_clockwise`
+    /// keys moving the camera down and up instead
+    pub allow_rotate: bool,
+    /// sensitivity is calculated by mx + c where (m: f32, c: f32)
+    /// and x is the camera distance, see [`KeyboardConf::move_speed`]
+    pub move_sensitivity: (f32, f32),
+    /// Pan further per update the further the camera is from the rig, using
+    /// the whole `move_sensitivity` formula. When `false` only c is used and
+    /// panning is the same speed at every zoom
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::{compute_rig_delta, CameraRig, CameraRigInput, CameraRigPose};
+    /// # use bevy_tests::KeyboardConf;
+    /// let mut keys = Input::default();
+    /// keys.press(KeyCode::W);
+    /// let panned = |rig: &CameraRig, rig_height: f32, camera_distance: f32| {
+    ///     let offset = Vec3::new(-1., 1., 0.).normalize() * camera_distance;
+    ///     let pose = CameraRigPose {
+    ///         rig: Transform::from_xyz(0., rig_height, 0.),
+    ///         camera: Some(Transform::from_translation(offset).looking_at(Vec3::ZERO, Vec3::Y)),
+    ///         ..Default::default()
+    ///     };
+    ///     let buttons = Input::default();
+    ///     let input = CameraRigInput::new(&rig.keyboard, &rig.mouse, &keys, &buttons);
+    ///     compute_rig_delta(rig, &pose, &input).0.translation.x
+    /// };
+    ///
+    /// let coupled = CameraRig {
+    ///     keyboard: KeyboardConf::default().with_move_sensitivity(0.01, 0.1),
+    ///     ..Default::default()
+    /// };
+    /// // Distance * m + c from the camera's distance, whatever the rig's height
+    /// assert!((panned(&coupled, 0., 100.) - 1.1).abs() < 0.0001);
+    /// assert!((panned(&coupled, 0., 200.) - 2.1).abs() < 0.0001);
+    /// assert!((panned(&coupled, 30., 100.) - 1.1).abs() < 0.0001);
+    ///
+    /// let constant = CameraRig {
+    ///     keyboard: coupled.keyboard.clone().with_zoom_coupled_sensitivity(false),
+    ///     ..Default::default()
+    /// };
+    /// assert!((panned(&constant, 0., 100.) - 0.1).abs() < 0.0001);
+    /// assert!((panned(&constant, 0., 200.) - 0.1).abs() < 0.0001);
+    /// ```
+    pub zoom_coupled_sensitivity: bool,
+    /// Fastest the keys and [`CameraRig::edge_scroll`] pan the rig in world
+    /// units per second, whatever the sensitivity works out as, for cameras
+    /// zoomed very far out. The default `f32::MAX` leaves it uncapped
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::{compute_rig_delta, CameraRig, CameraRigInput, CameraRigPose};
+    /// # use bevy_tests::{KeyboardConf, MouseConf};
+    /// let rig = CameraRig {
+    ///     keyboard: KeyboardConf::default().with_max_pan_speed(60.),
+    ///     mouse: MouseConf::default().with_max_drag_speed(120.),
+    ///     ..Default::default()
+    /// };
+    /// let mut keys = Input::default();
+    /// keys.press(KeyCode::W);
+    /// let pose = CameraRigPose {
+    ///     camera: Some(Transform::from_xyz(-5000., 5000., 0.).looking_at(Vec3::ZERO, Vec3::Y)),
+    ///     ..Default::default()
+    /// };
+    /// let moved = |keys: &Input<KeyCode>, drag_motion: Vec2| {
+    ///     let buttons = Input::default();
+    ///     let input = CameraRigInput {
+    ///         mouse_motion: drag_motion,
+    ///         drag_motion,
+    ///         ..CameraRigInput::new(&rig.keyboard, &rig.mouse, keys, &buttons)
+    ///     };
+    ///     compute_rig_delta(&rig, &pose, &input).0.translation.length()
+    /// };
+    /// // A second's worth each update at most
+    /// assert!((moved(&keys, Vec2::ZERO) - 1.).abs() < 0.0001);
+    /// assert!((moved(&Input::default(), Vec2::new(0., 5000.)) - 2.).abs() < 0.0001);
+    /// ```
+    #[doc(alias = "max_pan_speed_per_second")]
+    #[cfg_attr(feature = "inspector", inspector(min = 0.))]
+    pub max_pan_speed: f32,
+    /// Pan along world axes whichever way the rig is facing, forward along
+    /// `Vec3::X` and right along `Vec3::Z`, or the rig's `map_forward` and
+    /// `map_right` taken as world directions when set
+    #[doc(alias = "translate_local_vs_world")]
+    pub world_aligned: bool,
+    /// Held to multiply the move sensitivity by `sprint_multiplier`
+    pub sprint_modifier: Vec<KeyCode>,
+    #[cfg_attr(feature = "inspector", inspector(min = 1., max = 10.))]
+    pub sprint_multiplier: f32,
+    /// Held to multiply the move sensitivity by `fine_move_multiplier`, this
+    /// takes priority when the sprint modifier is also held
+    pub fine_move_modifier: Vec<KeyCode>,
+    #[cfg_attr(feature = "inspector", inspector(min = 0., max = 1.))]
+    pub fine_move_multiplier: f32,
+    pub clockwise: Vec<KeyCode>,
+    pub counter_clockwise: Vec<KeyCode>,
+    /// Radians per second
+    #[cfg_attr(feature = "inspector", inspector(min = 0., max = 2. * std::f32::consts::PI))]
+    pub rotate_sensitivity: f32,
+    /// Scales `rotate_sensitivity` by mx + c where (m: f32, c: f32) and x is
+    /// the camera distance, stopping at zero. A negative m slows rotation as
+    /// the camera zooms out, with c picked so the scale is 1 at the usual
+    /// distance. `None`, the default, keeps it the same at every zoom
+    ///
+    /// ```
+    /// # use bevy_tests::KeyboardConf;
+    /// // Full speed at a distance of 100, half at 600
+    /// let keyboard = KeyboardConf::default().with_rotate_distance_scale(-0.001, 1.1);
+    /// ```
+    pub rotate_distance_scale: Option<(f32, f32)>,
+    /// Furthest in radians the rotation target can get ahead of the rig
+    #[cfg_attr(feature = "inspector", inspector(min = 0., max = std::f32::consts::PI))]
+    pub rotate_max_lead: f32,
+    /// Starts or stops following the entity with [`CameraRigFollow`]
+    pub toggle_follow: Vec<KeyCode>,
+    /// Pans to the entity with [`CameraRigFollow`] once, without following it
+    pub center_on_target: Vec<KeyCode>,
+    /// Switches to and from [`CameraMode::FreeFly`]
+    pub toggle_free_fly: Vec<KeyCode>,
+    /// Held to pan freely while following with [`FollowDynamics::Locked`]
+    pub hold_to_unlock: Vec<KeyCode>,
+}
+
+impl Default for KeyboardConf {
+    fn default() -> Self {
+        KeyboardConf {
+            forward: vec![KeyCode::W, KeyCode::Up],
+            backward: vec![KeyCode::S, KeyCode::Down],
+            left: vec![KeyCode::A, KeyCode::Left],
+            right: vec![KeyCode::D, KeyCode::Right],
+            allow_pan: true,
+            allow_strafe: true,
+            allow_rotate: true,
+            move_sensitivity: (0.0009, 0.0045),
+            zoom_coupled_sensitivity: true,
+            max_pan_speed: f32::MAX,
+            world_aligned: false,
+            sprint_modifier: Vec::new(),
+            sprint_multiplier: 3.,
+            fine_move_modifier: Vec::new(),
+            fine_move_multiplier: 0.25,
+            clockwise: vec![KeyCode::Q],
+            counter_clockwise: vec![KeyCode::E],
+            rotate_sensitivity: std::f32::consts::PI,
+            rotate_distance_scale: None,
+            rotate_max_lead: 0.5,
+            toggle_follow: vec![KeyCode::F],
+            center_on_target: Vec::new(),
+            toggle_free_fly: Vec::new(),
+            hold_to_unlock: vec![KeyCode::Space],
+        }
+    }
+}
+
+/// Actions that can be bound to keys or mouse buttons
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ActionName {
+    Forward,
+    Backward,
+    Left,
+    Right,
+    Sprint,
+    FineMove,
+    RotateClockwise,
+    RotateCounterClockwise,
+    ToggleFollow,
+    CenterOnTarget,
+    ToggleFreeFly,
+    HoldToUnlock,
+    Rotate,
+    Drag,
+    PitchOnly,
+}
+
+impl KeyboardConf {
+    /// Every field has a `with_*` method, for changing a few of them from the
+    /// defaults
+    ///
+    /// ```
+    /// # use bevy::prelude::KeyCode;
+    /// # use bevy_tests::KeyboardConf;
+    /// let conf = KeyboardConf::default()
+    ///     .with_forward([KeyCode::Up])
+    ///     .with_sprint_modifier([KeyCode::LShift]);
+    /// assert_eq!(conf.clone(), conf);
+    /// assert_ne!(conf, KeyboardConf::default());
+    /// ```
+    pub fn with_forward(mut self, keys: impl Into<Vec<KeyCode>>) -> Self {
+        self.forward = keys.into();
+        self
+    }
+
+    pub fn with_backward(mut self, keys: impl Into<Vec<KeyCode>>) -> Self {
+        self.backward = keys.into();
+        self
+    }
+
+    pub fn with_left(mut self, keys: impl Into<Vec<KeyCode>>) -> Self {
+        self.left = keys.into();
+        self
+    }
+
+    pub fn with_right(mut self, keys: impl Into<Vec<KeyCode>>) -> Self {
+        self.right = keys.into();
+        self
+    }
+
+    pub fn with_allow_pan(mut self, allow_pan: bool) -> Self {
+        self.allow_pan = allow_pan;
+        self
+    }
+
+    pub fn with_allow_strafe(mut self, allow_strafe: bool) -> Self {
+        self.allow_strafe = allow_strafe;
+        self
+    }
+
+    pub fn with_allow_rotate(mut self, allow_rotate: bool) -> Self {
+        self.allow_rotate = allow_rotate;
+        self
+    }
+
+    pub fn with_move_sensitivity(mut self, m: f32, c: f32) -> Self {
+        self.move_sensitivity = (m, c);
+        self
+    }
+
+    pub fn with_zoom_coupled_sensitivity(mut self, zoom_coupled_sensitivity: bool) -> Self {
+        self.zoom_coupled_sensitivity = zoom_coupled_sensitivity;
+        self
+    }
+
+    pub fn with_max_pan_speed(mut self, max_pan_speed: f32) -> Self {
+        self.max_pan_speed = max_pan_speed;
+        self
+    }
+
+    pub fn with_world_aligned(mut self, world_aligned: bool) -> Self {
+        self.world_aligned = world_aligned;
+        self
+    }
+
+    pub fn with_sprint_modifier(mut self, keys: impl Into<Vec<KeyCode>>) -> Self {
+        self.sprint_modifier = keys.into();
+        self
+    }
+
+    pub fn with_sprint_multiplier(mut self, sprint_multiplier: f32) -> Self {
+        self.sprint_multiplier = sprint_multiplier;
+        self
+    }
+
+    pub fn with_fine_move_modifier(mut self, keys: impl Into<Vec<KeyCode>>) -> Self {
+        self.fine_move_modifier = keys.into();
+        self
+    }
+
+    pub fn with_fine_move_multiplier(mut self, fine_move_multiplier: f32) -> Self {
+        self.fine_move_multiplier = fine_move_multiplier;
+        self
+    }
+
+    pub fn with_clockwise(mut self, keys: impl Into<Vec<KeyCode>>) -> Self {
+        self.clockwise = keys.into();
+        self
+    }
+
+    pub fn with_counter_clockwise(mut self, keys: impl Into<Vec<KeyCode>>) -> Self {
+        self.counter_clockwise = keys.into();
+        self
+    }
+
+    pub fn with_rotate_sensitivity(mut self, rotate_sensitivity: f32) -> Self {
+        self.rotate_sensitivity = rotate_sensitivity;
+        self
+    }
+
+    pub fn with_rotate_distance_scale(mut self, m: f32, c: f32) -> Self {
+        self.rotate_distance_scale = Some((m, c));
+        self
+    }
+
+    pub fn with_rotate_max_lead(mut self, rotate_max_lead: f32) -> Self {
+        self.rotate_max_lead = rotate_max_lead;
+        self
+    }
+
+    pub fn with_toggle_follow(mut self, keys: impl Into<Vec<KeyCode>>) -> Self {
+        self.toggle_follow = keys.into();
+        self
+    }
+
+    pub fn with_center_on_target(mut self, keys: impl Into<Vec<KeyCode>>) -> Self {
+        self.center_on_target = keys.into();
+        self
+    }
+
+    pub fn with_toggle_free_fly(mut self, keys: impl Into<Vec<KeyCode>>) -> Self {
+        self.toggle_free_fly = keys.into();
+        self
+    }
+
+    pub fn with_hold_to_unlock(mut self, keys: impl Into<Vec<KeyCode>>) -> Self {
+        self.hold_to_unlock = keys.into();
+        self
+    }
+
+    /// Distance the move keys pan per update with the camera
+    /// `camera_distance` from the rig, before any multipliers
+    pub fn move_speed(&self, camera_distance: f32) -> f32 {
+        let (m, c) = self.move_sensitivity;
+        if self.zoom_coupled_sensitivity {
+            camera_distance * m + c
+        } else {
+            c
+        }
+    }
+
+    /// Whether the keys bound to `action` do anything in `camera_mode`, see
+    /// [`KeyboardConf::allow_pan`]
+    fn allows(&self, action: ActionName, camera_mode: CameraMode) -> bool {
+        match action {
+            ActionName::Forward | ActionName::Backward => self.allow_pan,
+            ActionName::Left | ActionName::Right => match camera_mode {
+                CameraMode::Orbit(_) => self.allow_rotate,
+                _ => self.allow_strafe,
+            },
+            ActionName::RotateClockwise | ActionName::RotateCounterClockwise => self.allow_rotate,
+            _ => true,
+        }
+    }
+
+    /// Keys bound to each action, for showing or rebinding controls
+    ///
+    /// ```
+    /// # use bevy::prelude::KeyCode;
+    /// # use bevy_tests::{ActionName, KeyboardConf};
+    /// let conf = KeyboardConf::default();
+    /// assert!(conf
+    ///     .bindings()
+    ///     .contains(&(ActionName::Forward, &[KeyCode::W, KeyCode::Up][..])));
+    /// ```
+    pub fn bindings(&self) -> Vec<(ActionName, &[KeyCode])> {
+        vec![
+            (ActionName::Forward, &self.forward),
+            (ActionName::Backward, &self.backward),
+            (ActionName::Left, &self.left),
+            (ActionName::Right, &self.right),
+            (ActionName::Sprint, &self.sprint_modifier),
+            (ActionName::FineMove, &self.fine_move_modifier),
+            (ActionName::RotateClockwise, &self.clockwise),
+            (ActionName::RotateCounterClockwise, &self.counter_clockwise),
+            (ActionName::ToggleFollow, &self.toggle_follow),
+            (ActionName::CenterOnTarget, &self.center_on_target),
+            (ActionName::ToggleFreeFly, &self.toggle_free_fly),
+            (ActionName::HoldToUnlock, &self.hold_to_unlock),
+        ]
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "inspector",
+    derive(Reflect, FromReflect, InspectorOptions),
+    reflect(InspectorOptions)
+)]
+pub struct MouseConf {
+    /// Any of these buttons rotates the rig
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::{compute_rig_delta, CameraRig, CameraRigInput, CameraRigPose, MouseConf};
+    /// let rig = CameraRig {
+    ///     mouse: MouseConf::default().with_rotate([MouseButton::Right, MouseButton::Middle]),
+    ///     ..Default::default()
+    /// };
+    /// let yaw_with = |button: MouseButton| {
+    ///     let mut buttons = Input::default();
+    ///     buttons.press(button);
+    ///     let keys = Input::default();
+    ///     let input = CameraRigInput {
+    ///         mouse_motion: Vec2::new(100., 0.),
+    ///         ..CameraRigInput::new(&rig.keyboard, &rig.mouse, &keys, &buttons)
+    ///     };
+    ///     let rotation = compute_rig_delta(&rig, &CameraRigPose::default(), &input).0.rotation;
+    ///     rotation.to_euler(EulerRot::YXZ).0
+    /// };
+    /// assert!(yaw_with(MouseButton::Right) != 0.);
+    /// assert_eq!(yaw_with(MouseButton::Middle), yaw_with(MouseButton::Right));
+    /// assert_eq!(yaw_with(MouseButton::Left), 0.);
+    /// ```
+    pub rotate: Vec<MouseButton>,
+    #[cfg_attr(feature = "inspector", inspector(min = 0., max = std::f32::consts::PI))]
+    pub rotate_sensitivity: f32,
+    /// Scales `rotate_sensitivity` by mx + c where (m: f32, c: f32) and x is
+    /// the camera distance, stopping at zero. `None` keeps it the same at
+    /// every zoom
+    pub rotate_distance_scale: Option<(f32, f32)>,
+    /// Button that only pitches the camera, ignoring horizontal movement so
+    /// the view can be tilted without turning
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::{compute_rig_delta, CameraRig, CameraRigInput, CameraRigPose, MouseConf};
+    /// let rig = CameraRig {
+    ///     mouse: MouseConf::default().with_pitch_only_button(MouseButton::Middle),
+    ///     ..Default::default()
+    /// };
+    /// let camera = Transform::from_xyz(-10., 10., 0.).looking_at(Vec3::ZERO, Vec3::Y);
+    /// let mut buttons = Input::default();
+    /// buttons.press(MouseButton::Middle);
+    /// let keys = Input::default();
+    /// let input = CameraRigInput {
+    ///     mouse_motion: Vec2::new(100., 50.),
+    ///     ..CameraRigInput::new(&rig.keyboard, &rig.mouse, &keys, &buttons)
+    /// };
+    /// let pose = CameraRigPose {
+    ///     camera: Some(camera),
+    ///     ..Default::default()
+    /// };
+    /// let (move_to_rig, move_to_camera) = compute_rig_delta(&rig, &pose, &input);
+    /// assert_eq!(move_to_rig.rotation, Quat::IDENTITY);
+    /// assert!(move_to_camera.translation.y > camera.translation.y + 0.1);
+    /// ```
+    pub pitch_only_button: Option<MouseButton>,
+    /// Rotate with the middle button, as in 3D modelling tools, along with the
+    /// `rotate` buttons. Without a `rotate_modifier` or `drag_modifier` a
+    /// button can't both rotate and drag, so while this is set the middle
+    /// button doesn't drag even when it is in `drag`, and a warning is logged
+    /// for rigs spawned like that
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::MouseConf;
+    /// let mouse = MouseConf::default().with_middle_drag_rotates(true);
+    /// assert!(mouse.rotate_buttons().any(|button| *button == MouseButton::Middle));
+    ///
+    /// let both = mouse.with_drag([MouseButton::Left, MouseButton::Middle]);
+    /// assert_eq!(both.drag_buttons().collect::<Vec<_>>(), [&MouseButton::Left]);
+    /// ```
+    #[doc(alias = "enable_rotation_on_middle_drag")]
+    pub middle_drag_rotates: bool,
+    /// Keys, one of which has to be held for the rotate buttons to rotate.
+    /// With `drag_modifier` this lets one button do both, picked when it
+    /// goes down and kept until it is released. When both could apply the
+    /// rig rotates, unless only `drag_modifier` is set, and a warning is
+    /// logged for rigs spawned sharing a button without modifiers to tell
+    /// them apart
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::{
+    /// #     compute_rig_delta, CameraRig, CameraRigInput, CameraRigPose, MouseConf, MouseGesture,
+    /// # };
+    /// // Middle drag pans and alt middle drag rotates
+    /// let rig = CameraRig {
+    ///     mouse: MouseConf::default()
+    ///         .with_rotate([MouseButton::Middle])
+    ///         .with_drag([MouseButton::Middle])
+    ///         .with_rotate_modifier([KeyCode::LAlt]),
+    ///     ..Default::default()
+    /// };
+    /// let mut buttons = Input::default();
+    /// buttons.press(MouseButton::Middle);
+    /// let mut keys = Input::default();
+    /// assert_eq!(rig.mouse.gesture(&buttons, &keys), Some(MouseGesture::Drag));
+    /// keys.press(KeyCode::LAlt);
+    /// assert_eq!(rig.mouse.gesture(&buttons, &keys), Some(MouseGesture::Rotate));
+    ///
+    /// // A drag that was started before alt went down keeps dragging
+    /// let yaw = |mouse_gesture: Option<MouseGesture>| {
+    ///     let input = CameraRigInput {
+    ///         mouse_motion: Vec2::new(100., 0.),
+    ///         ..CameraRigInput::new(&rig.keyboard, &rig.mouse, &keys, &buttons)
+    ///     };
+    ///     let pose = CameraRigPose {
+    ///         mouse_gesture,
+    ///         ..Default::default()
+    ///     };
+    ///     let rotation = compute_rig_delta(&rig, &pose, &input).0.rotation;
+    ///     rotation.to_euler(EulerRot::YXZ).0
+    /// };
+    /// assert!(yaw(None) != 0.);
+    /// assert_eq!(yaw(Some(MouseGesture::Drag)), 0.);
+    /// ```
+    #[doc(alias = "orbit_modifier")]
+    pub rotate_modifier: Option<Vec<KeyCode>>,
+    /// Any of these buttons drags the rig
+    pub drag: Vec<MouseButton>,
+    /// Keys, one of which has to be held for the drag buttons to drag, see
+    /// `rotate_modifier`
+    #[doc(alias = "pan_modifier")]
+    pub drag_modifier: Option<Vec<KeyCode>>,
+    /// sensitivity is calculated by mx + c where (m: f32, c: f32)
+    /// and x is the camera distance
+    pub drag_sensitivity: (f32, f32),
+    /// Fastest dragging pans the rig in world units per second, see
+    /// [`KeyboardConf::max_pan_speed`]
+    #[cfg_attr(feature = "inspector", inspector(min = 0.))]
+    pub max_drag_speed: f32,
+    /// Pixels the mouse has to move with the drag button held before panning
+    /// starts, so clicks aren't mistaken for drags
+    #[cfg_attr(feature = "inspector", inspector(min = 0., max = 100.))]
+    pub drag_threshold: f32,
+    /// Drag along the rig's `map_forward` and right directions, or its local
+    /// X and Z when those aren't set, instead of the camera's screen
+    /// directions projected onto the ground
+    pub legacy_drag_mapping: bool,
+    /// Divide mouse movement by the window height before applying the rotate
+    /// and drag sensitivities, so they are fractions of the viewport rather
+    /// than per pixel
+    pub normalize_to_viewport: bool,
+    /// Fraction of the camera's distance from the rig each wheel line zooms
+    /// by
+    #[cfg_attr(feature = "inspector", inspector(min = 0., max = 1.))]
+    pub zoom_sensitivity: f32,
+    /// Reverses the zoom direction of the mouse wheel
+    pub zoom_invert_wheel: bool,
+    pub zoom_anchor: ZoomAnchor,
+    /// (min, max) distance of the camera from the rig
+    pub zoom_limits: Option<(f32, f32)>,
+    /// (min, max) camera pitch in radians, when set zooming in past the min
+    /// zoom limit tilts the camera towards the max pitch instead, with the
+    /// distance from the min limit down to zero spanning the whole range.
+    /// Zooming out first tilts back down to the min pitch
+    pub over_zoom_tilt: Option<(f32, f32)>,
+    /// Tilts the camera as the mouse wheel zooms it, usually towards top down
+    /// when zooming out. Pitching by hand overrides it until the next zoom,
+    /// see [`ZoomPitchConf::snap_back`]. It is applied after `over_zoom_tilt`,
+    /// so zooming past the min limit with both set tilts by both, set one or
+    /// the other
+    #[doc(alias = "auto_tilt")]
+    #[doc(alias = "auto_tilt_on_zoom")]
+    pub zoom_pitch: Option<ZoomPitchConf>,
+    /// Discrete camera distances in ascending order, each scroll moves to the
+    /// next step instead of zooming by `zoom_sensitivity`
+    pub zoom_steps: Option<Vec<f32>>,
+    /// Point the rig rotates around, for both mouse and keyboard rotation
+    pub orbit_pivot: OrbitPivot,
+    /// Zoom by changing a perspective camera's fov rather than its distance
+    pub fov_zoom: Option<FovZoomConf>,
+    /// Hide the cursor while the rotate button is held
+    pub hide_cursor_during_rotate: bool,
+    /// Lock the cursor in place while the rotate button is held
+    pub cursor_lock_during_rotate: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "inspector", derive(Reflect, FromReflect))]
+pub struct FovZoomConf {
+    /// Radians per scroll
+    pub sensitivity: f32,
+    /// (min, max) fov in radians
+    pub limits: (f32, f32),
+    /// How quickly the fov eases towards its target, per second
+    pub smoothing: f32,
+}
+
+impl Default for FovZoomConf {
+    fn default() -> Self {
+        FovZoomConf {
+            sensitivity: 0.01,
+            limits: (0.02, 0.5),
+            smoothing: 10.,
+        }
+    }
+}
+
+/// Camera pitch for each zoom distance, going from `near_pitch` at
+/// `near_distance` or closer to `far_pitch` at `far_distance` or further.
+/// Pitches are in radians above the horizon, and a `curve` of
+/// [`SmootherKind::Linear`] maps distance to pitch linearly
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tests::{compute_rig_delta, CameraRig, CameraRigInput, CameraRigPose};
+/// # use bevy_tests::{MouseConf, ZoomPitchConf};
+/// let rig = CameraRig {
+///     mouse: MouseConf::default().with_zoom_pitch(ZoomPitchConf::default()),
+///     ..Default::default()
+/// };
+/// let camera = Transform::from_xyz(-75., 75., 0.).looking_at(Vec3::ZERO, Vec3::Y);
+/// let pitch_after = |scroll: f32| {
+///     let pose = CameraRigPose {
+///         camera: Some(camera),
+///         ..Default::default()
+///     };
+///     let keys = Input::default();
+///     let buttons = Input::default();
+///     let input = CameraRigInput {
+///         mouse_wheel: &[scroll],
+///         ..CameraRigInput::new(&rig.keyboard, &rig.mouse, &keys, &buttons)
+///     };
+///     let offset = compute_rig_delta(&rig, &pose, &input).1.translation;
+///     (offset.y / offset.length()).asin()
+/// };
+/// let pitch = std::f32::consts::FRAC_PI_4;
+/// assert!(pitch_after(-1.) > pitch + 0.01);
+/// assert!(pitch_after(0.5) < pitch - 0.01);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "inspector", derive(Reflect, FromReflect))]
+#[doc(alias = "AutoTilt")]
+pub struct ZoomPitchConf {
+    pub near_distance: f32,
+    pub far_distance: f32,
+    pub near_pitch: f32,
+    pub far_pitch: f32,
+    /// Shape of the change in pitch between the two distances
+    #[cfg_attr(feature = "inspector", reflect(ignore))]
+    pub curve: SmootherKind,
+    /// Zooming after pitching by hand goes back to the pitch for the new
+    /// distance, rather than keeping the difference
+    pub snap_back: bool,
+}
+
+impl Default for ZoomPitchConf {
+    fn default() -> Self {
+        ZoomPitchConf {
+            near_distance: 20.,
+            far_distance: 200.,
+            near_pitch: 0.5,
+            far_pitch: 1.2,
+            curve: SmootherKind::SmoothStep,
+            snap_back: false,
+        }
+    }
+}
+
+impl ZoomPitchConf {
+    pub fn pitch_at(&self, distance: f32) -> f32 {
+        let t = ((distance - self.near_distance)
+            / (self.far_distance - self.near_distance).max(f32::EPSILON))
+        .clamp(0., 1.);
+        self.near_pitch + (self.far_pitch - self.near_pitch) * apply_smoothstep(&self.curve, t)
+    }
+}
+
+/// Point the rig rotates around. The ground variants cast a ray onto the
+/// [`GroundPlane`], falling back to `RigOrigin` when it misses. Rotating by
+/// `q` around a pivot `p` moves the rig from `t` to `p + q * (t - p)` as well
+/// as rotating it, so the pivot stays at the same place on screen
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "inspector", derive(Reflect, FromReflect))]
+pub enum OrbitPivot {
+    #[default]
+    RigOrigin,
+    /// The ground at the center of the camera's view
+    ScreenCenterGround,
+    /// The ground under the cursor
+    CursorGround,
+}
+
+/// Point the camera zooms towards. The ground variants move the rig as well
+/// so the point stays put on screen, falling back to `ViewAxis` when the ray
+/// onto the [`GroundPlane`] misses
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "inspector", derive(Reflect, FromReflect))]
+pub enum ZoomAnchor {
+    /// The rig, along the camera's offset from it
+    #[default]
+    ViewAxis,
+    /// The ground at the center of the camera's view
+    GroundCenter,
+    /// The ground under the cursor
+    Cursor,
+}
+
+impl Default for MouseConf {
+    fn default() -> Self {
+        MouseConf {
+            rotate: vec![MouseButton::Right],
+            rotate_sensitivity: std::f32::consts::PI / 1000.,
+            rotate_distance_scale: None,
+            pitch_only_button: None,
+            middle_drag_rotates: false,
+            rotate_modifier: None,
+            drag: vec![MouseButton::Left],
+            drag_modifier: None,
+            drag_sensitivity: (1., std::f32::consts::PI / 1000.),
+            max_drag_speed: f32::MAX,
+            drag_threshold: 0.,
+            legacy_drag_mapping: false,
+            normalize_to_viewport: false,
+            zoom_sensitivity: 0.1,
+            zoom_invert_wheel: false,
+            zoom_anchor: ZoomAnchor::ViewAxis,
+            zoom_limits: None,
+            over_zoom_tilt: None,
+            zoom_pitch: None,
+            zoom_steps: None,
+            orbit_pivot: OrbitPivot::RigOrigin,
+            fov_zoom: None,
+            hide_cursor_during_rotate: false,
+            cursor_lock_during_rotate: false,
+        }
+    }
+}
+
+impl MouseConf {
+    /// Every field has a `with_*` method, for changing a few of them from the
+    /// defaults. Optional fields are set to `Some`
+    ///
+    /// ```
+    /// # use bevy_tests::MouseConf;
+    /// let conf = MouseConf::default()
+    ///     .with_zoom_limits(5., 100.)
+    ///     .with_zoom_steps([10., 40., 100.]);
+    /// assert_eq!(conf.zoom_steps, Some(vec![10., 40., 100.]));
+    /// assert_ne!(conf, MouseConf::default());
+    /// ```
+    pub fn with_rotate(mut self, buttons: impl Into<Vec<MouseButton>>) -> Self {
+        self.rotate = buttons.into();
+        self
+    }
+
+    pub fn with_rotate_sensitivity(mut self, rotate_sensitivity: f32) -> Self {
+        self.rotate_sensitivity = rotate_sensitivity;
+        self
+    }
+
+    pub fn with_rotate_distance_scale(mut self, m: f32, c: f32) -> Self {
+        self.rotate_distance_scale = Some((m, c));
+        self
+    }
+
+    pub fn with_pitch_only_button(mut self, button: MouseButton) -> Self {
+        self.pitch_only_button = Some(button);
+        self
+    }
+
+    pub fn with_middle_drag_rotates(mut self, middle_drag_rotates: bool) -> Self {
+        self.middle_drag_rotates = middle_drag_rotates;
+        self
+    }
+
+    pub fn with_rotate_modifier(mut self, keys: impl Into<Vec<KeyCode>>) -> Self {
+        self.rotate_modifier = Some(keys.into());
+        self
+    }
+
+    pub fn with_drag(mut self, buttons: impl Into<Vec<MouseButton>>) -> Self {
+        self.drag = buttons.into();
+        self
+    }
+
+    pub fn with_drag_modifier(mut self, keys: impl Into<Vec<KeyCode>>) -> Self {
+        self.drag_modifier = Some(keys.into());
+        self
+    }
+
+    pub fn with_drag_sensitivity(mut self, m: f32, c: f32) -> Self {
+        self.drag_sensitivity = (m, c);
+        self
+    }
+
+    pub fn with_max_drag_speed(mut self, max_drag_speed: f32) -> Self {
+        self.max_drag_speed = max_drag_speed;
+        self
+    }
+
+    pub fn with_drag_threshold(mut self, drag_threshold: f32) -> Self {
+        self.drag_threshold = drag_threshold;
+        self
+    }
+
+    pub fn with_legacy_drag_mapping(mut self, legacy_drag_mapping: bool) -> Self {
+        self.legacy_drag_mapping = legacy_drag_mapping;
+        self
+    }
+
+    pub fn with_normalize_to_viewport(mut self, normalize_to_viewport: bool) -> Self {
+        self.normalize_to_viewport = normalize_to_viewport;
+        self
+    }
+
+    pub fn with_zoom_sensitivity(mut self, zoom_sensitivity: f32) -> Self {
+        self.zoom_sensitivity = zoom_sensitivity;
+        self
+    }
+
+    pub fn with_zoom_invert_wheel(mut self, zoom_invert_wheel: bool) -> Self {
+        self.zoom_invert_wheel = zoom_invert_wheel;
+        self
+    }
+
+    pub fn with_zoom_anchor(mut self, zoom_anchor: ZoomAnchor) -> Self {
+        self.zoom_anchor = zoom_anchor;
+        self
+    }
+
+    pub fn with_zoom_limits(mut self, min: f32, max: f32) -> Self {
+        self.zoom_limits = Some((min, max));
+        self
+    }
+
+    pub fn with_over_zoom_tilt(mut self, min: f32, max: f32) -> Self {
+        self.over_zoom_tilt = Some((min, max));
+        self
+    }
+
+    pub fn with_zoom_steps(mut self, zoom_steps: impl Into<Vec<f32>>) -> Self {
+        self.zoom_steps = Some(zoom_steps.into());
+        self
+    }
+
+    pub fn with_orbit_pivot(mut self, orbit_pivot: OrbitPivot) -> Self {
+        self.orbit_pivot = orbit_pivot;
+        self
+    }
+
+    pub fn with_zoom_pitch(mut self, zoom_pitch: ZoomPitchConf) -> Self {
+        self.zoom_pitch = Some(zoom_pitch);
+        self
+    }
+
+    pub fn with_fov_zoom(mut self, fov_zoom: FovZoomConf) -> Self {
+        self.fov_zoom = Some(fov_zoom);
+        self
+    }
+
+    pub fn with_hide_cursor_during_rotate(mut self, hide_cursor_during_rotate: bool) -> Self {
+        self.hide_cursor_during_rotate = hide_cursor_during_rotate;
+        self
+    }
+
+    pub fn with_cursor_lock_during_rotate(mut self, cursor_lock_during_rotate: bool) -> Self {
+        self.cursor_lock_during_rotate = cursor_lock_during_rotate;
+        self
+    }
+
+    /// Mouse buttons bound to each action, for showing or rebinding controls
+    pub fn bindings(&self) -> Vec<(ActionName, &[MouseButton])> {
+        vec![
+            (ActionName::Rotate, &self.rotate),
+            (ActionName::Drag, &self.drag),
+            (ActionName::PitchOnly, self.pitch_only_button.as_slice()),
+        ]
+    }
+
+    /// Buttons that rotate the rig, `rotate` and the middle button when
+    /// `middle_drag_rotates` is set
+    pub fn rotate_buttons(&self) -> impl Iterator<Item = &MouseButton> {
+        self.rotate
+            .iter()
+            .chain(self.middle_drag_rotates.then_some(&MouseButton::Middle))
+    }
+
+    /// Buttons that drag the rig, `drag` without any taken by
+    /// `middle_drag_rotates` when there are no modifiers to tell them apart
+    pub fn drag_buttons(&self) -> impl Iterator<Item = &MouseButton> {
+        let middle_rotates = self.middle_drag_rotates
+            && self.rotate_modifier.is_none()
+            && self.drag_modifier.is_none();
+        self.drag
+            .iter()
+            .filter(move |button| !(middle_rotates && **button == MouseButton::Middle))
+    }
+
+    /// What the mouse buttons and modifiers held would start doing. When they
+    /// could do both it rotates, unless dragging has a modifier held and
+    /// rotating has none
+    pub fn gesture(
+        &self,
+        buttons: &Input<MouseButton>,
+        keys: &Input<KeyCode>,
+    ) -> Option<MouseGesture> {
+        let modifier_held = |modifier: &Option<Vec<KeyCode>>| match modifier {
+            Some(modifier) => modifier.iter().any(|key| keys.pressed(*key)),
+            None => true,
+        };
+        let rotate = modifier_held(&self.rotate_modifier)
+            && self.rotate_buttons().any(|button| buttons.pressed(*button));
+        let drag = modifier_held(&self.drag_modifier)
+            && self.drag_buttons().any(|button| buttons.pressed(*button));
+        let drag_first = self.rotate_modifier.is_none() && self.drag_modifier.is_some();
+        match (rotate, drag) {
+            (true, true) if drag_first => Some(MouseGesture::Drag),
+            (true, _) => Some(MouseGesture::Rotate),
+            (false, true) => Some(MouseGesture::Drag),
+            (false, false) => None,
+        }
+    }
+
+    /// Whether the buttons for `gesture` are still held
+    fn gesture_held(&self, gesture: MouseGesture, buttons: &Input<MouseButton>) -> bool {
+        match gesture {
+            MouseGesture::Rotate => self.rotate_buttons().any(|button| buttons.pressed(*button)),
+            MouseGesture::Drag => self.drag_buttons().any(|button| buttons.pressed(*button)),
+        }
+    }
+}
+
+/// What the mouse is doing while a button is held, see
+/// [`MouseConf::rotate_modifier`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MouseGesture {
+    Rotate,
+    Drag,
+}
+
+/// Discrete camera actions that can be bound to gamepad buttons
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "inspector", derive(Reflect, FromReflect))]
+pub enum CameraAction {
+    /// Turn the rig a quarter turn clockwise
+    SnapRotateClockwise,
+    /// Turn the rig a quarter turn counter clockwise
+    SnapRotateCounterClockwise,
+    /// Move back to the pose the rig was spawned with
+    ResetView,
+    ZoomIn,
+    ZoomOut,
+    /// Follow the [`SelectedEntity`], or stop following it
+    ToggleFollowSelected,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "inspector", derive(Reflect, FromReflect))]
+pub struct GamepadConf {
+    /// Buttons on any gamepad that trigger each action when pressed, none by
+    /// default so the game's own buttons are left alone. See
+    /// [`GamepadConf::standard`] for a ready made set
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::{CameraAction, GamepadConf};
+    /// let mut gamepad = GamepadConf::default();
+    /// gamepad
+    ///     .button_bindings
+    ///     .insert(CameraAction::ResetView, vec![GamepadButtonType::South]);
+    /// ```
+    pub button_bindings: HashMap<CameraAction, Vec<GamepadButtonType>>,
+    /// Each zoom step moves the camera to this much of its distance from the
+    /// rig, or out by its inverse. Rigs with `MouseConf::zoom_steps` move
+    /// between those instead
+    pub zoom_step_ratio: f32,
+    /// Swaps what the `ZoomIn` and `ZoomOut` buttons do, as
+    /// [`MouseConf::zoom_invert_wheel`] does for the wheel
+    pub zoom_invert_gamepad: bool,
+}
+
+impl Default for GamepadConf {
+    fn default() -> Self {
+        GamepadConf {
+            button_bindings: HashMap::new(),
+            zoom_step_ratio: 0.8,
+            zoom_invert_gamepad: false,
+        }
+    }
+}
+
+impl GamepadConf {
+    /// The triggers snap rotate, Select resets the view, up and down on the
+    /// DPad zoom and the north face button follows the [`SelectedEntity`]
+    pub fn standard() -> Self {
+        GamepadConf {
+            button_bindings: HashMap::from_iter([
+                (
+                    CameraAction::SnapRotateClockwise,
+                    vec![GamepadButtonType::RightTrigger],
+                ),
+                (
+                    CameraAction::SnapRotateCounterClockwise,
+                    vec![GamepadButtonType::LeftTrigger],
+                ),
+                (CameraAction::ResetView, vec![GamepadButtonType::Select]),
+                (CameraAction::ZoomIn, vec![GamepadButtonType::DPadUp]),
+                (CameraAction::ZoomOut, vec![GamepadButtonType::DPadDown]),
+                (
+                    CameraAction::ToggleFollowSelected,
+                    vec![GamepadButtonType::North],
+                ),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_button_bindings(
+        mut self,
+        button_bindings: impl Into<HashMap<CameraAction, Vec<GamepadButtonType>>>,
+    ) -> Self {
+        self.button_bindings = button_bindings.into();
+        self
+    }
+
+    pub fn with_zoom_step_ratio(mut self, zoom_step_ratio: f32) -> Self {
+        self.zoom_step_ratio = zoom_step_ratio;
+        self
+    }
+
+    pub fn with_zoom_invert_gamepad(mut self, zoom_invert_gamepad: bool) -> Self {
+        self.zoom_invert_gamepad = zoom_invert_gamepad;
+        self
+    }
+}
+
+/// Only holds config, so comparing or cloning rigs ignores where they are
+/// moving to, which is kept in [`CameraRigTarget`]
+#[derive(Component, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "inspector",
+    derive(Reflect, InspectorOptions),
+    reflect(Component, InspectorOptions)
+)]
+pub struct CameraRig {
+    pub keyboard: KeyboardConf,
+    pub mouse: MouseConf,
+    pub gamepad: GamepadConf,
+    pub disable: bool,
+    /// Only move a child camera on one of these layers, for a rig with more
+    /// than one camera under it, as in multi-pass rendering. A camera without
+    /// `RenderLayers` is on layer 0. `None` moves the first camera found
+    ///
+    /// ```
+    /// # use bevy::{prelude::*, render::view::RenderLayers};
+    /// # use bevy_tests::{CameraRig, CameraRigBundle};
+    /// # let mut world = World::new();
+    /// world
+    ///     .spawn(CameraRigBundle {
+    ///         camera_rig: CameraRig {
+    ///             render_layers: Some(RenderLayers::layer(1)),
+    ///             ..Default::default()
+    ///         },
+    ///         ..Default::default()
+    ///     })
+    ///     .with_children(|rig| {
+    ///         // Left where it is
+    ///         rig.spawn(Camera3dBundle::default());
+    ///         // Moved by the rig
+    ///         rig.spawn((Camera3dBundle::default(), RenderLayers::layer(1)));
+    ///     });
+    /// ```
+    #[cfg_attr(feature = "inspector", reflect(ignore))]
+    pub render_layers: Option<RenderLayers>,
+    /// While [`CameraRigTarget::focus_on_entity`] or
+    /// [`CameraRigTarget::playback_path`] is moving the rig, ignore the
+    /// keyboard, mouse and gamepad, as in a tutorial. When `false` any input cancels
+    /// the animation and hands control back, without a
+    /// [`FocusOnEntityCompleted`]
+    ///
+    /// ```
+    /// # use bevy_tests::CameraRig;
+    /// // A tutorial flying the player over the map
+    /// let rig = CameraRig {
+    ///     disable_input_during_animation: true,
+    ///     ..Default::default()
+    /// };
+    /// ```
+    #[doc(alias = "disable_during_animation")]
+    pub disable_input_during_animation: bool,
+    /// Rig local direction moved in by the forward keys, when `None` this is
+    /// up the screen along the ground
+    pub map_forward: Option<Vec3>,
+    /// Rig local direction moved in by the right keys, when `None` this is
+    /// `map_forward` crossed with `Vec3::Y`. Only used with `map_forward`
+    pub map_right: Option<Vec3>,
+    /// Smoothly animate between `zoom_steps` rather than snapping to them
+    pub zoom_step_animation: bool,
+    /// Ascending camera distances that separate zoom levels, a
+    /// [`ZoomLevelChanged`] is sent when the camera crosses one
+    pub zoom_level_thresholds: Vec<f32>,
+    /// Keeps the rig at this height no matter how it is moved
+    pub lock_rig_height: Option<f32>,
+    /// Height of the ground the camera has to keep in view. The camera is kept
+    /// above it and tilted at least slightly downwards
+    pub pan_floor: Option<f32>,
+    /// Lowest the camera can get above the [`GroundPlane`]. Zooming or
+    /// pitching below it tilts the camera up around the rig instead, then
+    /// backs it away once it is looking nearly straight down
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::{compute_rig_delta, CameraRig, CameraRigInput, CameraRigPose};
+    /// let limited = CameraRig {
+    ///     min_camera_height: Some(5.),
+    ///     ..Default::default()
+    /// };
+    /// let camera = Transform::from_xyz(-10., 10., 0.).looking_at(Vec3::ZERO, Vec3::Y);
+    /// let mut buttons = Input::default();
+    /// buttons.press(MouseButton::Right);
+    /// let camera_height = |rig: &CameraRig, scroll: &[f32], pitch: f32| {
+    ///     let pose = CameraRigPose {
+    ///         camera: Some(camera),
+    ///         ..Default::default()
+    ///     };
+    ///     let keys = Input::default();
+    ///     let input = CameraRigInput {
+    ///         mouse_motion: Vec2::new(0., pitch),
+    ///         mouse_wheel: scroll,
+    ///         ..CameraRigInput::new(&rig.keyboard, &rig.mouse, &keys, &buttons)
+    ///     };
+    ///     compute_rig_delta(rig, &pose, &input).1.translation.y
+    /// };
+    ///
+    /// // Zooming in, pitching down, and both at once
+    /// for (scroll, pitch) in [(&[9.][..], 0.), (&[], -200.), (&[5.], -100.)] {
+    ///     assert!(camera_height(&CameraRig::default(), scroll, pitch) < 5.);
+    ///     assert!(camera_height(&limited, scroll, pitch) > 4.999);
+    /// }
+    /// ```
+    pub min_camera_height: Option<f32>,
+    /// Sets an orthographic camera's scale to its distance from the rig times
+    /// this, so zooming changes what it shows. Moving an orthographic camera
+    /// closer doesn't, see [`CameraRigBundle::orthographic`]
+    pub ortho_scale_per_distance: Option<f32>,
+    /// World units per pixel, for orthographic cameras showing pixel art.
+    /// When set the rig is shown on the pixel grid along the camera's right
+    /// and up, at its own height, so sprites don't shimmer, after following
+    /// too, while it keeps moving by less than a pixel an update underneath
+    /// so slow pans still get there. See the pixel_art example
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::CameraRigBundle;
+    /// let (mut rig, camera) = CameraRigBundle::orthographic(Vec3::new(-10., 10., 0.), 20.);
+    /// // Two pixels to a world unit
+    /// rig.camera_rig.pixel_snap = Some(0.5);
+    /// ```
+    pub pixel_snap: Option<f32>,
+    /// How a rig with `pixel_snap` turns
+    pub pixel_snap_rotation: PixelSnapRotation,
+    /// Seconds after manually panning before following resumes, when zero
+    /// panning stops following altogether
+    #[cfg_attr(feature = "inspector", inspector(min = 0., max = 10.))]
+    pub follow_activation_delay_secs: f32,
+    /// Seconds following takes to ramp up to full speed when it starts or
+    /// resumes, so the rig doesn't lurch towards the followed entity
+    #[cfg_attr(feature = "inspector", inspector(min = 0., max = 10.))]
+    pub follow_ease_in_secs: f32,
+    pub follow_dynamics: FollowDynamics,
+    /// Distance from the followed entity at which the rig stops easing and
+    /// jumps onto it. Scale this with the world, a large world needs a larger
+    /// one to ever settle and a small world a smaller one to not jump early
+    ///
+    /// ```
+    /// # use bevy_tests::CameraRig;
+    /// // A world measured in kilometres
+    /// let rig = CameraRig {
+    ///     follow_snap_epsilon: 5.,
+    ///     ..Default::default()
+    /// };
+    /// ```
+    #[cfg_attr(feature = "inspector", inspector(min = 0., max = 10.))]
+    pub follow_snap_epsilon: f32,
+    /// Entity the rig sits exactly on every update, for cutscenes, see
+    /// [`CameraRig::lock_to_entity`]
+    pub position_lock: Option<Entity>,
+    /// Area on the ground the rig is kept inside of
+    pub bounds: Option<BoundsShape>,
+    /// Let the rig leave the bounds and pull it back in, rather than stopping
+    /// it at the edge. The pull builds up the rig's momentum over several
+    /// updates, so [`compute_rig_delta`] leaves the rig where it went
+    pub spring_return_to_bounds: bool,
+    pub bounds_conf: BoundsConf,
+    /// Whether `bounds` keeps in the rig or everything the camera shows
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::{BoundsMode, CameraRig};
+    /// // Never show past the edge of the map, zooming in if it has to
+    /// let mut rig = CameraRig {
+    ///     bounds_mode: BoundsMode::VisibleArea { clamp_zoom: true },
+    ///     ..Default::default()
+    /// };
+    /// rig.set_rect_bounds(Rect::from_center_size(Vec2::ZERO, Vec2::splat(400.)));
+    /// ```
+    pub bounds_mode: BoundsMode,
+    /// Pan when the cursor is near the edge of the window
+    pub edge_scroll: Option<EdgeScrollConf>,
+    pub camera_mode: CameraMode,
+    /// Level the camera every frame, as lerping rotations can slowly add roll,
+    /// and only let the rig turn around `Vec3::Y`
+    #[doc(alias = "world_up_locked")]
+    pub stabilize_roll: bool,
+    /// Pan along the ground using only which way the rig and `map_forward`
+    /// face around `Vec3::Y`, so a rig authored pitched or a tilted
+    /// `map_forward` doesn't pan into or out of the ground
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::{compute_rig_delta, CameraRig, CameraRigInput, CameraRigPose};
+    /// let mut keys = Input::default();
+    /// keys.press(KeyCode::W);
+    /// let panned = |rig: &CameraRig| {
+    ///     let pose = CameraRigPose {
+    ///         rig: Transform::from_rotation(Quat::from_rotation_z(-0.5)),
+    ///         ..Default::default()
+    ///     };
+    ///     let buttons = Input::default();
+    ///     let input = CameraRigInput::new(&rig.keyboard, &rig.mouse, &keys, &buttons);
+    ///     compute_rig_delta(rig, &pose, &input).0.translation
+    /// };
+    ///
+    /// let pitched = CameraRig {
+    ///     stabilize_roll: false,
+    ///     ..Default::default()
+    /// };
+    /// let level = panned(&pitched);
+    /// assert!(level.x > 0.);
+    /// assert_eq!(level.y, 0.);
+    ///
+    /// let tilted = CameraRig {
+    ///     map_forward: Some(Vec3::new(1., -1., 0.)),
+    ///     ..pitched.clone()
+    /// };
+    /// assert_eq!(panned(&tilted).y, 0.);
+    ///
+    /// let into_ground = CameraRig {
+    ///     pan_ignores_pitch: false,
+    ///     ..pitched
+    /// };
+    /// assert!(panned(&into_ground).y < 0.);
+    /// ```
+    pub pan_ignores_pitch: bool,
+    /// Ease the rig, camera and fov at rates that bring them all to rest on
+    /// the same update, rather than each at the same rate and settling
+    /// whenever it gets close enough
+    ///
+    /// ```
+    /// # use bevy_tests::CameraRig;
+    /// let rig = CameraRig {
+    ///     sync_smoothing: true,
+    ///     ..Default::default()
+    /// };
+    /// ```
+    pub sync_smoothing: bool,
+    /// Eases each move of the rig and camera along this curve, from when
+    /// their target changes until they get there. `Linear` closes the same
+    /// fraction of the gap every update
+    #[cfg_attr(feature = "inspector", reflect(ignore))]
+    pub smooth_step_mode: SmootherKind,
+    /// How quickly the camera eases to a new distance from the rig, per
+    /// second, separately from it turning and pitching. `None` eases the
+    /// distance along with the rest of the camera. Ignored with
+    /// `sync_smoothing`, which eases everything together
+    ///
+    /// ```
+    /// # use bevy_tests::CameraRig;
+    /// // Zoom snappily while turning gently
+    /// let rig = CameraRig {
+    ///     zoom_smoothing: Some(30.),
+    ///     ..Default::default()
+    /// };
+    /// ```
+    pub zoom_smoothing: Option<f32>,
+    /// Updates after the rig spawns that it and its camera go straight to
+    /// their targets, so a level starts with the camera in place rather than
+    /// sliding there
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::{CameraRig, CameraRigBundle, CameraRigTarget};
+    /// // Start the level over the capital
+    /// let rig = CameraRigBundle {
+    ///     camera_rig: CameraRig {
+    ///         spawn_warmup_frames: 2,
+    ///         ..Default::default()
+    ///     },
+    ///     target: CameraRigTarget {
+    ///         rig: Some(Transform::from_xyz(50., 0., 20.)),
+    ///         ..Default::default()
+    ///     },
+    ///     ..Default::default()
+    /// };
+    /// ```
+    pub spawn_warmup_frames: u32,
+    /// Seconds between samples when recording a path, see
+    /// [`CameraRigTarget::record_path`]
+    #[cfg_attr(feature = "inspector", inspector(min = 0., max = 10.))]
+    pub record_interval_secs: f32,
+    /// Ease, follow and animate in game time, so the camera slows down and
+    /// stops along with `Time`'s relative speed. Otherwise the camera keeps
+    /// to real time and stays responsive in slow motion or while paused
+    ///
+    /// ```
+    /// # use bevy_tests::CameraRig;
+    /// // A replay camera that pauses along with the game
+    /// let rig = CameraRig {
+    ///     use_scaled_time: true,
+    ///     ..Default::default()
+    /// };
+    /// ```
+    pub use_scaled_time: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "inspector", derive(Reflect, FromReflect))]
+pub enum CameraMode {
+    /// The rig pans over the map
+    #[default]
+    Pan,
+    /// The rig stays put and the camera circles it, with the left and right
+    /// keys changing the azimuth and forward and backward the polar angle
+    Orbit(OrbitConf),
+    /// For debugging, the camera flies around on its own with the movement
+    /// keys following its view, counter clockwise and clockwise moving up and
+    /// down, and the rotate button looking around freely. It flies at the
+    /// speed the move keys panned at when it was switched to. Switching back
+    /// moves the rig to where the camera was looking
+    FreeFly,
+}
+
+/// How the rig is pulled back inside [`CameraRig::bounds`] with
+/// [`CameraRig::spring_return_to_bounds`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "inspector",
+    derive(Reflect, FromReflect, InspectorOptions),
+    reflect(InspectorOptions)
+)]
+pub struct BoundsConf {
+    /// Acceleration towards the bounds per unit the rig is outside them,
+    /// added to its momentum every update
+    #[cfg_attr(feature = "inspector", inspector(min = 0., max = 100.))]
+    pub spring_stiffness: f32,
+}
+
+impl Default for BoundsConf {
+    fn default() -> Self {
+        BoundsConf {
+            spring_stiffness: 5.,
+        }
+    }
+}
+
+/// Angles are in radians, with the polar angle measured down from straight
+/// above the rig
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "inspector", derive(Reflect, FromReflect))]
+pub struct OrbitConf {
+    pub min_polar: f32,
+    pub max_polar: f32,
+    pub min_radius: f32,
+    pub max_radius: f32,
+}
+
+impl Default for OrbitConf {
+    fn default() -> Self {
+        OrbitConf {
+            min_polar: 0.1,
+            max_polar: std::f32::consts::FRAC_PI_2 - 0.1,
+            min_radius: 1.,
+            max_radius: 200.,
+        }
+    }
+}
+
+/// Panning speed is full within `outer_margin` pixels of the window edge and
+/// ramps down to nothing at `inner_margin` pixels from the edge
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "inspector", derive(Reflect, FromReflect))]
+pub struct EdgeScrollConf {
+    pub outer_margin: f32,
+    pub inner_margin: f32,
+    /// Exponent applied to the ramp between the margins, higher values stay
+    /// slow for longer
+    pub curve: f32,
+    /// sensitivity is calculated by mx + c where (m: f32, c: f32)
+    /// and x is the camera distance, capped at
+    /// [`KeyboardConf::max_pan_speed`]
+    pub sensitivity: (f32, f32),
+}
+
+impl Default for EdgeScrollConf {
+    fn default() -> Self {
+        EdgeScrollConf {
+            outer_margin: 10.,
+            inner_margin: 40.,
+            curve: 2.,
+            sensitivity: (0.0009, 0.0045),
+        }
+    }
+}
+
+impl EdgeScrollConf {
+    /// Push for each axis between -1 and 1 with y pointing up the screen, for
+    /// a cursor at `position` in a window of `size`
+    pub fn push(&self, position: Vec2, size: Vec2) -> Vec2 {
+        let ramp = |distance: f32| {
+            if distance <= self.outer_margin {
+                1.
+            } else if distance >= self.inner_margin {
+                0.
+            } else {
+                ((self.inner_margin - distance) / (self.inner_margin - self.outer_margin))
+                    .powf(self.curve)
+            }
+        };
+        let push = Vec2::new(
+            ramp(size.x - position.x) - ramp(position.x),
+            ramp(size.y - position.y) - ramp(position.y),
+        );
+        if push.length_squared() > 1. {
+            push.normalize()
+        } else {
+            push
+        }
+    }
+}
+
+/// Curve applied to the smoothing of a rig, see [`apply_smoothstep`]. Custom
+/// curves are equal when they point to the same function
+#[derive(Clone, Copy, Debug, Default)]
+pub enum SmootherKind {
+    #[default]
+    Linear,
+    /// 3t² - 2t³
+    SmoothStep,
+    /// 6t⁵ - 15t⁴ + 10t³
+    SmootherStep,
+    Custom(fn(f32) -> f32),
+}
+
+impl PartialEq for SmootherKind {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (SmootherKind::Custom(a), SmootherKind::Custom(b)) => *a as usize == *b as usize,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+/// Remaps `t` along `kind`. The step curves clamp `t` to 0..=1 first
+///
+/// ```
+/// # use bevy_tests::{apply_smoothstep, SmootherKind};
+/// assert_eq!(apply_smoothstep(&SmootherKind::Linear, 0.25), 0.25);
+/// assert_eq!(apply_smoothstep(&SmootherKind::SmoothStep, 0.5), 0.5);
+/// assert!(apply_smoothstep(&SmootherKind::SmootherStep, 0.25) < 0.25);
+/// assert_eq!(apply_smoothstep(&SmootherKind::Custom(|t| t * t), 0.5), 0.25);
+/// ```
+pub fn apply_smoothstep(kind: &SmootherKind, t: f32) -> f32 {
+    match kind {
+        SmootherKind::Linear => t,
+        SmootherKind::SmoothStep => {
+            let t = t.clamp(0., 1.);
+            t * t * (3. - 2. * t)
+        }
+        SmootherKind::SmootherStep => {
+            let t = t.clamp(0., 1.);
+            t * t * t * (t * (t * 6. - 15.) + 10.)
+        }
+        SmootherKind::Custom(curve) => curve(t),
+    }
+}
+
+/// How a rig with [`CameraRig::pixel_snap`] turns, as anything but quarter
+/// turns shows the pixel grid at an angle
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "inspector", derive(Reflect, FromReflect))]
+pub enum PixelSnapRotation {
+    /// The keyboard and mouse don't turn the rig
+    #[default]
+    Locked,
+    /// The rig turns as usual but is shown at the nearest quarter turn
+    QuarterTurns,
+}
+
+/// How the rig catches up with the entity it is following
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "inspector", derive(Reflect, FromReflect))]
+pub enum FollowDynamics {
+    /// Covers a fixed fraction of the remaining distance each frame
+    #[default]
+    Exponential,
+    /// Accelerates towards the entity by `stiffness` per unit of distance and
+    /// slows by `damping` per unit of velocity. Damping below
+    /// `2 * stiffness.sqrt()` overshoots before settling
+    Spring { stiffness: f32, damping: f32 },
+    /// Holds the rig exactly `offset` from the entity, in the rig's local
+    /// space. Panning doesn't stop following, instead holding
+    /// [`KeyboardConf::hold_to_unlock`] or pushing against the screen edge
+    /// with [`CameraRig::edge_scroll`] frees the rig until let go, when it
+    /// glides back and locks on again. Ignores
+    /// [`CameraRigFollow::keep_on_screen_margin`]
+    Locked { offset: Vec3 },
+}
+
+impl FollowDynamics {
+    /// A spring that settles as fast as possible without overshooting
+    pub fn critically_damped(stiffness: f32) -> Self {
+        FollowDynamics::Spring {
+            stiffness,
+            damping: 2. * stiffness.sqrt(),
+        }
+    }
+}
+
+/// Area in the XZ plane, with x and y of the `Vec2`s mapping to world x and z
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "inspector", derive(Reflect, FromReflect))]
+pub enum BoundsShape {
+    Rect(Rect),
+    Circle {
+        center: Vec2,
+        radius: f32,
+    },
+    /// Corners of a convex polygon in either winding order
+    Polygon(Vec<Vec2>),
+}
+
+impl BoundsShape {
+    /// The nearest point inside the bounds
+    ///
+    /// ```
+    /// # use bevy::prelude::Vec2;
+    /// # use bevy_tests::BoundsShape;
+    /// let triangle = BoundsShape::Polygon(vec![
+    ///     Vec2::new(0., 0.),
+    ///     Vec2::new(4., 0.),
+    ///     Vec2::new(0., 4.),
+    /// ]);
+    /// assert_eq!(triangle.clamp(Vec2::new(1., 1.)), Vec2::new(1., 1.));
+    /// assert_eq!(triangle.clamp(Vec2::new(2., -3.)), Vec2::new(2., 0.));
+    /// assert_eq!(triangle.clamp(Vec2::new(3., 3.)), Vec2::new(2., 2.));
+    /// ```
+    pub fn clamp(&self, point: Vec2) -> Vec2 {
+        match self {
+            BoundsShape::Rect(rect) => point.clamp(rect.min, rect.max),
+            BoundsShape::Circle { center, radius } => {
+                *center + (point - *center).clamp_length_max(*radius)
+            }
+            BoundsShape::Polygon(corners) => {
+                let edges = || {
+                    corners
+                        .iter()
+                        .zip(corners.iter().cycle().skip(1))
+                        .map(|(start, end)| (*start, *end))
+                };
+                let sides = edges().map(|(start, end)| (end - start).perp_dot(point - start));
+                if sides.clone().all(|side| side >= 0.) || sides.clone().all(|side| side <= 0.) {
+                    return point;
+                }
+                // Outside a convex polygon the nearest point is on an edge,
+                // reached by pushing back along that edge's normal
+                edges()
+                    .map(|(start, end)| {
+                        let edge = end - start;
+                        let t = ((point - start).dot(edge)
+                            / edge.length_squared().max(f32::EPSILON))
+                        .clamp(0., 1.);
+                        start + edge * t
+                    })
+                    .min_by(|a, b| {
+                        a.distance_squared(point)
+                            .total_cmp(&b.distance_squared(point))
+                    })
+                    .unwrap_or(point)
+            }
+        }
+    }
+}
+
+/// What [`CameraRig::bounds`] keeps inside of them
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "inspector", derive(Reflect, FromReflect))]
+pub enum BoundsMode {
+    /// The rig, while the camera can still see past the edge, especially
+    /// when zoomed out
+    #[default]
+    RigPoint,
+    /// The ground the camera shows, see [`ground_footprint`], so the rig is
+    /// kept further in as the camera zooms out. When the view is wider than
+    /// the bounds it is centered on them, and `clamp_zoom` also zooms the
+    /// camera in until it fits. Only applies to [`BoundsShape::Rect`] bounds
+    /// on rigs with a camera, others keep the rig in as with `RigPoint`
+    VisibleArea { clamp_zoom: bool },
+}
+
+impl Default for CameraRig {
+    fn default() -> Self {
+        CameraRig {
+            keyboard: KeyboardConf::default(),
+            mouse: MouseConf::default(),
+            gamepad: GamepadConf::default(),
+            disable: false,
+            render_layers: None,
+            disable_input_during_animation: false,
+            map_forward: None,
+            map_right: None,
+            zoom_step_animation: true,
+            zoom_level_thresholds: Vec::new(),
+            lock_rig_height: None,
+            pan_floor: None,
+            min_camera_height: None,
+            ortho_scale_per_distance: None,
+            pixel_snap: None,
+            pixel_snap_rotation: PixelSnapRotation::Locked,
+            follow_activation_delay_secs: 0.,
+            follow_ease_in_secs: 0.25,
+            follow_dynamics: FollowDynamics::Exponential,
+            follow_snap_epsilon: 0.005,
+            position_lock: None,
+            bounds: None,
+            spring_return_to_bounds: false,
+            bounds_conf: BoundsConf::default(),
+            bounds_mode: BoundsMode::RigPoint,
+            edge_scroll: None,
+            camera_mode: CameraMode::Pan,
+            stabilize_roll: true,
+            pan_ignores_pitch: true,
+            sync_smoothing: false,
+            smooth_step_mode: SmootherKind::Linear,
+            zoom_smoothing: None,
+            spawn_warmup_frames: 0,
+            record_interval_secs: 0.1,
+            use_scaled_time: true,
+        }
+    }
+}
+
+impl CameraRig {
+    /// Rig local forward and right directions, `None` when movement follows
+    /// the camera instead
+    pub fn map_directions(&self) -> Option<(Vec3, Vec3)> {
+        let forward = self.map_forward?;
+        Some((
+            forward,
+            self.map_right.unwrap_or_else(|| forward.cross(Vec3::Y)),
+        ))
+    }
+
+    /// The bounds and whether to zoom in to fit them, when they keep in the
+    /// ground the camera shows, see [`BoundsMode::VisibleArea`]
+    fn visible_area_bounds(&self) -> Option<(Rect, bool)> {
+        match (&self.bounds, self.bounds_mode) {
+            (Some(BoundsShape::Rect(rect)), BoundsMode::VisibleArea { clamp_zoom }) => {
+                Some((*rect, clamp_zoom))
+            }
+            _ => None,
+        }
+    }
+
+    /// Where the rig goes this update to get from `position` to `clamped`
+    /// inside the bounds, carrying `velocity` along, see
+    /// [`CameraRig::spring_return_to_bounds`]
+    fn return_to_bounds(
+        &self,
+        mut position: Vec2,
+        clamped: Vec2,
+        velocity: &mut Vec2,
+        delta_seconds: f32,
+    ) -> Vec2 {
+        if !self.spring_return_to_bounds || clamped.distance(position) <= 0.005 {
+            *velocity = Vec2::ZERO;
+            return clamped;
+        }
+        let stiffness = self.bounds_conf.spring_stiffness;
+        // Just enough damping that the rig comes to rest at the edge rather
+        // than bouncing off it
+        let damping = 2. * stiffness.sqrt();
+        let steps = (delta_seconds / SPRING_STEP).ceil().max(1.);
+        let dt = delta_seconds / steps;
+        for _ in 0..steps as u32 {
+            let force = (clamped - position) * stiffness;
+            *velocity += (force - *velocity * damping) * dt;
+            position += *velocity * dt;
+        }
+        position
+    }
+
+    /// Whether the rig moves a child camera on `layers`, `None` for a camera
+    /// without `RenderLayers`, see [`CameraRig::render_layers`]
+    pub fn controls_camera(&self, layers: Option<&RenderLayers>) -> bool {
+        self.render_layers
+            .is_none_or(|wanted| wanted.intersects(&layers.copied().unwrap_or_default()))
+    }
+
+    pub fn set_rect_bounds(&mut self, rect: Rect) {
+        self.bounds = Some(BoundsShape::Rect(rect));
+    }
+
+    pub fn set_circle_bounds(&mut self, center: Vec2, radius: f32) {
+        self.bounds = Some(BoundsShape::Circle { center, radius });
+    }
+
+    pub fn set_polygon_bounds(&mut self, corners: impl Into<Vec<Vec2>>) {
+        self.bounds = Some(BoundsShape::Polygon(corners.into()));
+    }
+
+    /// Moves the rig over `x`, `z`, clamped to `bounds`, keeping its height
+    /// and rotation and the camera's zoom. `animate` eases there, otherwise it
+    /// snaps on the next update along with anything else in flight. Useful for
+    /// jumping to a minimap click
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::{CameraRig, CameraRigTarget};
+    /// let mut rig = CameraRig::default();
+    /// rig.set_rect_bounds(Rect::new(-40., -100., 40., 100.));
+    /// let mut target = CameraRigTarget::default();
+    ///
+    /// // The minimap shows the whole map, clicked three quarters across
+    /// let map = Rect::new(-100., -100., 100., 100.);
+    /// let click = Vec2::new(0.75, 0.5);
+    /// let point = map.min + map.size() * click;
+    ///
+    /// let rig_transform = Transform::from_xyz(0., 5., 0.);
+    /// rig.move_to_world_xz(&mut target, &rig_transform, point.x, point.y, false);
+    /// assert_eq!(target.rig.unwrap().translation, Vec3::new(40., 5., 0.));
+    /// assert!(target.snap_next_update);
+    /// ```
+    pub fn move_to_world_xz(
+        &self,
+        target: &mut CameraRigTarget,
+        rig_transform: &Transform,
+        x: f32,
+        z: f32,
+        animate: bool,
+    ) {
+        let mut point = Vec2::new(x, z);
+        if let Some(bounds) = &self.bounds {
+            point = bounds.clamp(point);
+        }
+        let mut move_to_rig = target.rig.unwrap_or(*rig_transform);
+        move_to_rig.translation.x = point.x;
+        move_to_rig.translation.z = point.y;
+        target.rig = Some(move_to_rig);
+        target.snap_next_update |= !animate;
+    }
+
+    /// Puts the rig exactly on `entity` every update, without easing, until
+    /// [`CameraRig::unlock`]. This takes over from following and panning,
+    /// while the rig can still be turned and zoomed
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::CameraRig;
+    /// # let cart = Entity::PLACEHOLDER;
+    /// let mut rig = CameraRig::default();
+    /// rig.lock_to_entity(cart);
+    /// // Back to following and panning
+    /// rig.unlock();
+    /// ```
+    #[doc(alias = "hard_lock")]
+    pub fn lock_to_entity(&mut self, entity: Entity) {
+        self.position_lock = Some(entity);
+    }
+
+    /// Stops [`CameraRig::lock_to_entity`], leaving the rig where it is
+    pub fn unlock(&mut self) {
+        self.position_lock = None;
+    }
+
+    /// Sets the [`CameraShake::seed`] of the camera under `rig`, for when the
+    /// rig is at hand but not its camera
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::CameraRig;
+    /// fn replay_shake(mut commands: Commands, rigs: Query<Entity, With<CameraRig>>) {
+    ///     for rig in rigs.iter() {
+    ///         CameraRig::set_shake_seed(&mut commands, rig, 7);
+    ///     }
+    /// }
+    /// ```
+    pub fn set_shake_seed(commands: &mut Commands, rig: Entity, seed: u64) {
+        commands.add(SetShakeSeed { rig, seed });
+    }
+
+    /// Stops `rig` as [`CameraRigTarget::stop`] does and ends any
+    /// [`CameraShake`] on its camera, like [`CameraCommand::Stop`] for a rig
+    /// other than the active one
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::CameraRig;
+    /// fn skip_cutscene(mut commands: Commands, rigs: Query<Entity, With<CameraRig>>) {
+    ///     for rig in rigs.iter() {
+    ///         CameraRig::stop(&mut commands, rig);
+    ///     }
+    /// }
+    /// ```
+    pub fn stop(commands: &mut Commands, rig: Entity) {
+        commands.add(StopRig { rig });
+    }
+
+    /// Position of `world` in `window` with the origin at the bottom left,
+    /// matching `Window::cursor_position`. `None` when the point is behind the
+    /// camera
+    pub fn world_to_screen(
+        world: Vec3,
+        camera: &Camera,
+        camera_transform: &GlobalTransform,
+        window: &Window,
+    ) -> Option<Vec2> {
+        let view = camera_transform
+            .compute_matrix()
+            .inverse()
+            .transform_point3(world);
+        if view.z >= 0. {
+            return None;
+        }
+        let ndc = camera.projection_matrix().project_point3(view);
+        if ndc.is_nan() {
+            return None;
+        }
+        Some((ndc.truncate() + Vec2::ONE) / 2. * Vec2::new(window.width(), window.height()))
+    }
+
+    /// Time since the last update, scaled or not by `Time`'s relative speed,
+    /// see [`CameraRig::use_scaled_time`]
+    pub fn frame_time(&self, time: &Time) -> Duration {
+        if self.use_scaled_time {
+            time.delta()
+        } else {
+            time.raw_delta()
+        }
+    }
+
+    /// The rig's [`CameraRigSettings`]
+    pub fn settings(&self) -> CameraRigSettings {
+        CameraRigSettings {
+            keyboard: self.keyboard.clone(),
+            mouse: self.mouse.clone(),
+            gamepad: self.gamepad.clone(),
+            edge_scroll: self.edge_scroll,
+            follow_dynamics: self.follow_dynamics,
+            follow_activation_delay_secs: self.follow_activation_delay_secs,
+            follow_ease_in_secs: self.follow_ease_in_secs,
+            zoom_step_animation: self.zoom_step_animation,
+            sync_smoothing: self.sync_smoothing,
+            smooth_step_mode: self.smooth_step_mode,
+            zoom_smoothing: self.zoom_smoothing,
+        }
+    }
+
+    /// Switches a live rig to `settings`. Where the rig is heading, what it
+    /// follows and its home pose are kept in its [`CameraRigTarget`], so
+    /// anything in flight carries on and only new input uses the new settings
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::{CameraRig, CameraRigSettings, KeyboardConf};
+    /// let mut rig = CameraRig::default();
+    /// rig.set_circle_bounds(Vec2::ZERO, 500.);
+    /// let modern = CameraRigSettings {
+    ///     keyboard: KeyboardConf::default().with_world_aligned(true),
+    ///     sync_smoothing: true,
+    ///     ..Default::default()
+    /// };
+    /// rig.apply_settings(&modern);
+    /// assert_eq!(rig.settings(), modern);
+    /// assert!(rig.bounds.is_some());
+    /// ```
+    pub fn apply_settings(&mut self, settings: &CameraRigSettings) {
+        self.keyboard = settings.keyboard.clone();
+        self.mouse = settings.mouse.clone();
+        self.gamepad = settings.gamepad.clone();
+        self.edge_scroll = settings.edge_scroll;
+        self.follow_dynamics = settings.follow_dynamics;
+        self.follow_activation_delay_secs = settings.follow_activation_delay_secs;
+        self.follow_ease_in_secs = settings.follow_ease_in_secs;
+        self.zoom_step_animation = settings.zoom_step_animation;
+        self.sync_smoothing = settings.sync_smoothing;
+        self.smooth_step_mode = settings.smooth_step_mode;
+        self.zoom_smoothing = settings.zoom_smoothing;
+    }
+}
+
+/// The parts of a [`CameraRig`] a player would pick on an options screen: its
+/// keyboard, mouse, gamepad and edge scroll controls, how it follows and how
+/// it eases. The rest belongs to the level or the game and is left as the rig
+/// was set up, such as its bounds, camera mode, height limits, pixel snap,
+/// `pan_ignores_pitch`, `stabilize_roll` and `use_scaled_time`
+#[derive(Clone, Debug, PartialEq)]
+pub struct CameraRigSettings {
+    pub keyboard: KeyboardConf,
+    pub mouse: MouseConf,
+    pub gamepad: GamepadConf,
+    pub edge_scroll: Option<EdgeScrollConf>,
+    pub follow_dynamics: FollowDynamics,
+    pub follow_activation_delay_secs: f32,
+    pub follow_ease_in_secs: f32,
+    pub zoom_step_animation: bool,
+    pub sync_smoothing: bool,
+    pub smooth_step_mode: SmootherKind,
+    pub zoom_smoothing: Option<f32>,
+}
+
+impl Default for CameraRigSettings {
+    fn default() -> Self {
+        CameraRig::default().settings()
+    }
+}
+
+/// Settings applied to the [`ActiveCameraRig`] when a [`CameraSettingsChanged`]
+/// is sent or this is changed after being inserted
+#[derive(Resource, Clone, Debug, Default, PartialEq)]
+pub struct DefaultCameraSettings(pub CameraRigSettings);
+
+/// Applies the [`DefaultCameraSettings`] to the [`ActiveCameraRig`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CameraSettingsChanged;
+
+/// Runtime state of a [`CameraRig`]. `rig` and `camera` are the transforms
+/// they are smoothly moving towards, `None` when they are at rest. Other
+/// systems can write these to move the camera
+#[derive(Component, Default, Clone, Debug)]
+#[doc(alias = "move_to")]
+pub struct CameraRigTarget {
+    pub rig: Option<Transform>,
+    pub camera: Option<Transform>,
+    /// Started by manual panning, following is paused until it finishes
+    pub follow_reactivation_timer: Option<Timer>,
+    /// How far following has eased in, from 0 to 1. With
+    /// [`FollowDynamics::Locked`] this is 1 once locked on
+    pub follow_blend: f32,
+    /// Velocity of the rig when following with [`FollowDynamics::Spring`]
+    pub follow_velocity: Vec3,
+    /// Velocity over the ground of a rig being pulled back inside its bounds,
+    /// see [`CameraRig::spring_return_to_bounds`]
+    pub bounds_velocity: Vec2,
+    /// Freed from following with [`FollowDynamics::Locked`]
+    pub follow_unlocked: bool,
+    /// Offset from the locked position left to glide back with
+    /// [`FollowDynamics::Locked`]
+    pub follow_glide: Option<Vec3>,
+    /// Zoom level last seen, see [`CameraRig::zoom_level_thresholds`]
+    pub zoom_level: Option<u8>,
+    /// Move the rig and camera straight to their targets on the next update
+    /// rather than smoothly, then clear this
+    pub snap_next_update: bool,
+    /// Field of view the camera is easing towards when using fov zoom
+    pub fov: Option<f32>,
+    /// Pixels moved with the drag button held, until `dragging` starts
+    pub drag_distance: f32,
+    pub dragging: bool,
+    /// What the mouse buttons held are doing, picked when they went down so
+    /// letting go of a modifier part way through doesn't switch
+    pub mouse_gesture: Option<MouseGesture>,
+    /// Rig translation, where it is heading and the camera distance when the
+    /// wheel last zoomed towards a [`ZoomAnchor`]. Until the zoom finishes
+    /// the rig is moved as far towards its target as the camera has zoomed,
+    /// so the anchor stays put on screen however the zoom is eased
+    ///
+    /// ```
+    /// # use bevy_tests::{CameraRig, MouseConf, ZoomAnchor};
+    /// // Keep the ground at the center of the view in place while zooming
+    /// let rig = CameraRig {
+    ///     mouse: MouseConf::default().with_zoom_anchor(ZoomAnchor::GroundCenter),
+    ///     ..Default::default()
+    /// };
+    /// ```
+    pub zoom_focus: Option<(Vec3, Vec3, f32)>,
+    /// Rig and camera transforms the rig returns to on [`CameraCommand::Reset`],
+    /// recorded when the rig is first seen
+    pub home: Option<(Transform, Transform)>,
+    /// Mode and camera transform from before [`CameraMode::FreeFly`], restored
+    /// when leaving it
+    pub free_fly: Option<(CameraMode, Transform)>,
+    /// See [`CameraRigTarget::focus_on_entity`]
+    pub focus: Option<FocusAnimation>,
+    /// Updates until everything comes to rest with [`CameraRig::sync_smoothing`]
+    pub smoothing_steps: Option<f32>,
+    /// How far the current move is along [`CameraRig::smooth_step_mode`],
+    /// from 0 when the target changed towards 1
+    pub smoothing_progress: f32,
+    /// Updates left of [`CameraRig::spawn_warmup_frames`]
+    pub warmup_frames: u32,
+    /// See [`CameraRigTarget::record_path`]
+    pub recording: Option<CameraPathRecording>,
+    /// A recording that reached its max duration, until it is taken with
+    /// [`CameraRigTarget::stop_recording`]
+    pub recorded_path: Option<CameraPath>,
+    /// See [`CameraRigTarget::playback_path`]
+    pub path_playback: Option<CameraPathPlayback>,
+    /// Translation and rotation [`CameraRig::pixel_snap`] added to the rig's
+    /// transform this frame, removed before the next
+    pub pixel_snap_offset: (Vec3, Quat),
+}
+
+/// The old name of [`CameraRigTarget`]
+#[deprecated(note = "renamed to CameraRigTarget")]
+pub type MoveTarget = CameraRigTarget;
+
+/// A [`CameraRigTarget::focus_on_entity`] in progress
+#[derive(Clone, Debug)]
+pub struct FocusAnimation {
+    pub entity: Entity,
+    /// Distance of the camera from the rig once there
+    pub distance: f32,
+    /// Seconds to get there
+    pub duration: f32,
+    pub elapsed: f32,
+    /// Rig position and camera distance it started from
+    start: Option<(Vec3, f32)>,
+    /// Where the entity was last seen, so a despawned entity is still reached
+    last_position: Option<Vec3>,
+}
+
+impl CameraRigTarget {
+    /// Captures the rig for a save game. Anything still moving is saved as
+    /// where it was heading. `follow` is the game's own id for the followed
+    /// entity, as `Entity`s change between sessions
+    pub fn capture_state(
+        &self,
+        rig: &Transform,
+        camera: &Transform,
+        follow: Option<u64>,
+    ) -> CameraRigSaveState {
+        CameraRigSaveState {
+            rig: self.rig.unwrap_or(*rig),
+            camera: self.camera.unwrap_or(*camera),
+            follow,
+        }
+    }
+
+    /// Puts the rig straight back into a saved state without animating.
+    /// Following isn't restored, send [`CameraCommand::Follow`] for the
+    /// entity `state.follow` maps to
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::CameraRigTarget;
+    /// let rig = Transform::from_xyz(3., 0., 4.);
+    /// let camera = Transform::from_xyz(-10., 10., 0.).looking_at(Vec3::ZERO, Vec3::Y);
+    /// let mut target = CameraRigTarget {
+    ///     rig: Some(Transform::from_xyz(5., 0., 5.)),
+    ///     ..Default::default()
+    /// };
+    /// let state = target.capture_state(&rig, &camera, Some(7));
+    ///
+    /// let (mut loaded_rig, mut loaded_camera) = (Transform::default(), Transform::default());
+    /// target.apply_state(&state, &mut loaded_rig, &mut loaded_camera);
+    /// assert_eq!(loaded_rig, Transform::from_xyz(5., 0., 5.));
+    /// assert_eq!(loaded_camera, camera);
+    /// assert!(target.rig.is_none() && target.camera.is_none());
+    /// assert_eq!(target.capture_state(&loaded_rig, &loaded_camera, state.follow), state);
+    /// ```
+    pub fn apply_state(
+        &mut self,
+        state: &CameraRigSaveState,
+        rig: &mut Transform,
+        camera: &mut Transform,
+    ) {
+        *rig = state.rig;
+        *camera = state.camera;
+        self.rig = None;
+        self.camera = None;
+        self.fov = None;
+        self.follow_reactivation_timer = None;
+        self.follow_velocity = Vec3::ZERO;
+        self.follow_glide = None;
+        self.drag_distance = 0.;
+        self.dragging = false;
+        self.mouse_gesture = None;
+        self.zoom_focus = None;
+        self.focus = None;
+        self.path_playback = None;
+    }
+
+    /// Flies the rig over `entity` and zooms the camera to `zoom_distance`
+    /// over `duration` seconds, keeping the rig's height, then sends
+    /// [`FocusOnEntityCompleted`]. Manual movement is overridden until it
+    /// finishes. For jumping to a city or unit
+    pub fn focus_on_entity(&mut self, entity: Entity, zoom_distance: f32, duration: f32) {
+        self.focus = Some(FocusAnimation {
+            entity,
+            distance: zoom_distance,
+            duration,
+            elapsed: 0.,
+            start: None,
+            last_position: None,
+        });
+    }
+
+    /// Starts recording where the rig and its camera go, sampled every
+    /// [`CameraRig::record_interval_secs`], for previewing a cinematic in a
+    /// level editor. Recording stops after `max_duration` seconds or when
+    /// stopped
+    ///
+    /// ```
+    /// # use bevy_tests::CameraRigTarget;
+    /// let mut target = CameraRigTarget::default();
+    /// target.record_path(10.);
+    /// // Once the cinematic has been flown, play it back
+    /// if let Some(path) = target.stop_recording() {
+    ///     target.playback_path(path);
+    /// }
+    /// ```
+    pub fn record_path(&mut self, max_duration: f32) {
+        self.recording = Some(CameraPathRecording {
+            max_duration,
+            path: CameraPath::default(),
+            elapsed: 0.,
+            next_sample: 0.,
+        });
+        self.recorded_path = None;
+    }
+
+    /// Stops recording, returning what was recorded, or a recording that
+    /// already reached its max duration
+    pub fn stop_recording(&mut self) -> Option<CameraPath> {
+        self.recording
+            .take()
+            .map(|recording| recording.path)
+            .or_else(|| self.recorded_path.take())
+    }
+
+    /// Moves the rig and camera along `path`, overriding manual movement
+    /// until it finishes
+    pub fn playback_path(&mut self, path: CameraPath) {
+        self.path_playback = Some(CameraPathPlayback { path, elapsed: 0. });
+    }
+
+    /// Whether a focus or path playback is moving the rig
+    fn animating(&self) -> bool {
+        self.focus.is_some() || self.path_playback.is_some()
+    }
+
+    /// Stops the rig and camera where they are, dropping anything they were
+    /// heading towards including a focus or path playback. Unlike
+    /// [`CameraCommand::Snap`] they don't jump to their targets. Use
+    /// [`CameraRig::stop`] to end a [`CameraShake`] too
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::CameraRigTarget;
+    /// // Stop the camera where it is when the player clicks
+    /// fn stop_on_click(buttons: Res<Input<MouseButton>>, mut targets: Query<&mut CameraRigTarget>) {
+    ///     if buttons.just_pressed(MouseButton::Left) {
+    ///         for mut target in targets.iter_mut() {
+    ///             target.stop();
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    #[doc(alias = "cancel")]
+    pub fn stop(&mut self) {
+        self.rig = None;
+        self.camera = None;
+        self.fov = None;
+        self.snap_next_update = false;
+        self.follow_velocity = Vec3::ZERO;
+        self.bounds_velocity = Vec2::ZERO;
+        self.follow_glide = None;
+        self.smoothing_steps = None;
+        self.smoothing_progress = 0.;
+        self.zoom_focus = None;
+        self.focus = None;
+        self.path_playback = None;
+    }
+}
+
+/// Rig and camera transforms recorded over time, see
+/// [`CameraRigTarget::record_path`]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct CameraPath {
+    pub samples: Vec<CameraPathSample>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct CameraPathSample {
+    /// Seconds since recording started
+    pub elapsed: f32,
+    pub rig: Transform,
+    /// Relative to the rig
+    pub camera: Transform,
+}
+
+impl CameraPath {
+    pub fn duration(&self) -> f32 {
+        self.samples.last().map_or(0., |sample| sample.elapsed)
+    }
+
+    /// Rig and camera transforms `elapsed` seconds in, blending between the
+    /// samples either side. Translations follow a Catmull-Rom spline through
+    /// the samples, so the path curves smoothly rather than turning sharply
+    /// at each one
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::{CameraPath, CameraPathSample};
+    /// let path = CameraPath {
+    ///     samples: [(0., 0., 0.), (1., 10., 0.), (2., 10., 10.)]
+    ///         .map(|(elapsed, x, z)| CameraPathSample {
+    ///             elapsed,
+    ///             rig: Transform::from_xyz(x, 0., z),
+    ///             camera: Transform::from_xyz(-75., 75., 0.),
+    ///         })
+    ///         .to_vec(),
+    /// };
+    /// let (rig, _) = path.sample(1.).unwrap();
+    /// assert_eq!(rig.translation, Vec3::new(10., 0., 0.));
+    /// // Rounding the corner
+    /// let (rig, _) = path.sample(1.5).unwrap();
+    /// assert!(rig.translation.x > 10. && rig.translation.z < 5.);
+    /// ```
+    pub fn sample(&self, elapsed: f32) -> Option<(Transform, Transform)> {
+        let next = self
+            .samples
+            .iter()
+            .position(|sample| sample.elapsed > elapsed)
+            .unwrap_or(self.samples.len());
+        let after = self.samples.get(next);
+        let before = next
+            .checked_sub(1)
+            .and_then(|index| self.samples.get(index));
+        match (before, after) {
+            (Some(before), Some(after)) => {
+                let t = (elapsed - before.elapsed) / (after.elapsed - before.elapsed);
+                // The ends of the path repeat their last sample
+                let first = next
+                    .checked_sub(2)
+                    .and_then(|index| self.samples.get(index))
+                    .unwrap_or(before);
+                let last = self.samples.get(next + 1).unwrap_or(after);
+                let spline = |point: fn(&CameraPathSample) -> Vec3| {
+                    catmull_rom(
+                        [first, before, after, last].map(|sample| (sample.elapsed, point(sample))),
+                        elapsed,
+                    )
+                };
+                let mut rig = blend_transforms(&before.rig, &after.rig, t);
+                rig.translation = spline(|sample| sample.rig.translation);
+                let mut camera = blend_transforms(&before.camera, &after.camera, t);
+                camera.translation = spline(|sample| sample.camera.translation);
+                Some((rig, camera))
+            }
+            (Some(sample), None) | (None, Some(sample)) => Some((sample.rig, sample.camera)),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Point at `elapsed` on a Catmull-Rom spline between the middle two of
+/// `points`, given as times and positions. The tangents take the time between
+/// points into account, so unevenly spaced samples don't bunch up
+fn catmull_rom(points: [(f32, Vec3); 4], elapsed: f32) -> Vec3 {
+    let [(t0, p0), (t1, p1), (t2, p2), (t3, p3)] = points;
+    let span = t2 - t1;
+    let m1 = (p2 - p0) / (t2 - t0) * span;
+    let m2 = (p3 - p1) / (t3 - t1) * span;
+    let t = (elapsed - t1) / span;
+    let (t2, t3) = (t * t, t * t * t);
+    p1 * (2. * t3 - 3. * t2 + 1.)
+        + m1 * (t3 - 2. * t2 + t)
+        + p2 * (-2. * t3 + 3. * t2)
+        + m2 * (t3 - t2)
+}
+
+fn blend_transforms(a: &Transform, b: &Transform, t: f32) -> Transform {
+    Transform {
+        translation: a.translation.lerp(b.translation, t),
+        rotation: a.rotation.slerp(b.rotation, t),
+        scale: a.scale.lerp(b.scale, t),
+    }
+}
+
+/// A [`CameraRigTarget::record_path`] in progress
+#[derive(Clone, Debug)]
+pub struct CameraPathRecording {
+    pub max_duration: f32,
+    pub path: CameraPath,
+    elapsed: f32,
+    next_sample: f32,
+}
+
+/// A [`CameraRigTarget::playback_path`] in progress
+#[derive(Clone, Debug)]
+pub struct CameraPathPlayback {
+    pub path: CameraPath,
+    pub elapsed: f32,
+}
+
+/// A rig's pose for save games, see [`CameraRigTarget::capture_state`]
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct CameraRigSaveState {
+    pub rig: Transform,
+    /// Relative to the rig, so this includes the zoom
+    pub camera: Transform,
+    /// The game's id for the followed entity
+    pub follow: Option<u64>,
+}
+
+/// Overrides the input config of every rig while it is on top of the
+/// [`CameraInputContexts`] stack
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct CameraInputContext {
+    pub keyboard: Option<KeyboardConf>,
+    pub mouse: Option<MouseConf>,
+}
+
+/// Stack of input contexts, for temporary control schemes such as a build mode
+#[derive(Resource, Default)]
+pub struct CameraInputContexts {
+    stack: Vec<CameraInputContext>,
+}
+
+impl CameraInputContexts {
+    pub fn push_context(&mut self, context: CameraInputContext) {
+        self.stack.push(context);
+    }
+
+    pub fn pop_context(&mut self) -> Option<CameraInputContext> {
+        self.stack.pop()
+    }
+
+    pub fn top(&self) -> Option<&CameraInputContext> {
+        self.stack.last()
+    }
+
+    /// The keyboard config `rig` should use
+    pub fn keyboard<'a>(&'a self, rig: &'a CameraRig) -> &'a KeyboardConf {
+        self.top()
+            .and_then(|context| context.keyboard.as_ref())
+            .unwrap_or(&rig.keyboard)
+    }
+
+    /// The mouse config `rig` should use
+    pub fn mouse<'a>(&'a self, rig: &'a CameraRig) -> &'a MouseConf {
+        self.top()
+            .and_then(|context| context.mouse.as_ref())
+            .unwrap_or(&rig.mouse)
+    }
+}
+
+/// Multiplies the keyboard, drag and edge scroll speed of every rig, for
+/// speed boosts that shouldn't change each rig's config. Insert a
+/// [`CameraRigSpeedMultiplierReset`] to put it back after a while
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct CameraRigSpeedMultiplier(pub f32);
+
+impl Default for CameraRigSpeedMultiplier {
+    fn default() -> Self {
+        CameraRigSpeedMultiplier(1.)
+    }
+}
+
+/// Resets the [`CameraRigSpeedMultiplier`] to 1 when the timer finishes
+#[derive(Resource, Clone, Debug)]
+pub struct CameraRigSpeedMultiplierReset(pub Timer);
+
+impl CameraRigSpeedMultiplierReset {
+    pub fn from_seconds(duration: f32) -> Self {
+        CameraRigSpeedMultiplierReset(Timer::from_seconds(duration, TimerMode::Once))
+    }
+}
+
+/// Ticks the [`CameraRigSpeedMultiplierReset`] if there is one, and resets the
+/// multiplier and removes the timer once it finishes
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tests::{CameraRigSpeedMultiplier, CameraRigSpeedMultiplierReset};
+/// // A scout ability tripling camera speed for half a second
+/// fn scout(mut commands: Commands) {
+///     commands.insert_resource(CameraRigSpeedMultiplier(3.));
+///     commands.insert_resource(CameraRigSpeedMultiplierReset::from_seconds(0.5));
+/// }
+/// ```
+pub fn reset_camera_speed_multiplier_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut multiplier: ResMut<CameraRigSpeedMultiplier>,
+    reset: Option<ResMut<CameraRigSpeedMultiplierReset>>,
+) {
+    if let Some(mut reset) = reset {
+        if reset.0.tick(time.delta()).finished() {
+            multiplier.0 = 1.;
+            commands.remove_resource::<CameraRigSpeedMultiplierReset>();
+        }
+    }
+}
+
+/// Marks a camera spawned for a rig by
+/// [`FourXCameraPlugin::auto_attach_camera`]
+#[derive(Component, Default)]
+pub struct RigCamera;
+
+/// Marks a child of a [`CameraRig`] that the rig should leave alone,
+/// even if it has a `Camera`
+#[derive(Component, Default)]
+pub struct CameraRigIgnore;
+
+/// Finds the camera a [`CameraRig`] moves: the first of its children with a
+/// `Camera`, without [`CameraRigIgnore`] and on the rig's
+/// [`CameraRig::render_layers`]
+#[derive(SystemParam)]
+pub struct RigCameras<'w, 's> {
+    cameras: Query<'w, 's, Option<&'static RenderLayers>, (With<Camera>, Without<CameraRigIgnore>)>,
+}
+
+impl<'w, 's> RigCameras<'w, 's> {
+    pub fn get(&self, rig: &CameraRig, children: &Children) -> Option<Entity> {
+        children.iter().copied().find(|child| {
+            self.cameras
+                .get(*child)
+                .is_ok_and(|layers| rig.controls_camera(layers))
+        })
+    }
+}
+
+/// Movement and zoom make up for the rig's scale, including any scale
+/// inherited from its parents, but bounds and the camera's own scale don't,
+/// so a warning is logged for rigs that are scaled
+#[derive(Bundle, Default)]
+pub struct CameraRigBundle {
+    pub camera_rig: CameraRig,
+    pub target: CameraRigTarget,
+    pub ground_focus: CameraGroundFocus,
+    pub cursor: CameraRigCursor,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+}
+
+impl CameraRigBundle {
+    /// A rig and its orthographic camera child, `offset` from the rig and
+    /// showing `view_height` world units from top to bottom. Zooming, edge
+    /// scroll and rotation all work as with a perspective camera
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::CameraRigBundle;
+    /// fn setup(mut commands: Commands) {
+    ///     let (rig, camera) = CameraRigBundle::orthographic(Vec3::new(-10., 10., 0.), 20.);
+    ///     commands.spawn(rig).with_children(|rig| {
+    ///         rig.spawn(camera);
+    ///     });
+    /// }
+    /// ```
+    pub fn orthographic(offset: Vec3, view_height: f32) -> (Self, Camera3dBundle) {
+        (
+            CameraRigBundle {
+                camera_rig: CameraRig {
+                    ortho_scale_per_distance: Some(view_height / offset.length()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Camera3dBundle {
+                projection: OrthographicProjection {
+                    scale: view_height,
+                    scaling_mode: ScalingMode::FixedVertical(1.),
+                    ..Default::default()
+                }
+                .into(),
+                transform: Transform::from_translation(offset).looking_at(Vec3::ZERO, Vec3::Y),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Starts the rig at `start` and eases it into the bundle's transform
+    pub fn intro_from(mut self, start: Transform) -> Self {
+        self.target.rig = Some(self.transform);
+        self.transform = start;
+        self.global_transform = GlobalTransform::from(start);
+        self
+    }
+}
+
+/// A [`CameraRigBundle`] in [`CameraMode::Orbit`] around the world origin,
+/// with W/S changing the polar angle and A/D the azimuth
+#[derive(Bundle)]
+pub struct OrbitCameraRigBundle {
+    pub rig: CameraRigBundle,
+}
+
+impl OrbitCameraRigBundle {
+    /// The rig, and its camera child already at `radius` and `polar_angle`
+    /// from straight above the rig, turned to `azimuth`. Both are kept within
+    /// `limits`
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_tests::{OrbitCameraRigBundle, OrbitConf};
+    /// fn setup(mut commands: Commands) {
+    ///     let (rig, camera) = OrbitCameraRigBundle::new(50., 1., 0., OrbitConf::default());
+    ///     commands.spawn(rig).with_children(|rig| {
+    ///         rig.spawn(camera);
+    ///     });
+    /// }
+    /// ```
+    pub fn new(
+        radius: f32,
+        polar_angle: f32,
+        azimuth: f32,
+        limits: OrbitConf,
+    ) -> (Self, Camera3dBundle) {
+        let radius = radius.clamp(limits.min_radius, limits.max_radius);
+        let polar_angle = polar_angle.clamp(limits.min_polar, limits.max_polar);
+        let mut camera = Transform::from_xyz(-radius, 0., 0.).looking_at(Vec3::ZERO, Vec3::Y);
+        pitch_camera(&mut camera, std::f32::consts::FRAC_PI_2 - polar_angle);
+        (
+            OrbitCameraRigBundle {
+                rig: CameraRigBundle {
+                    camera_rig: CameraRig {
+                        keyboard: KeyboardConf {
+                            forward: vec![KeyCode::W],
+                            backward: vec![KeyCode::S],
+                            left: vec![KeyCode::A],
+                            right: vec![KeyCode::D],
+                            ..Default::default()
+                        },
+                        camera_mode: CameraMode::Orbit(limits),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_rotation(Quat::from_rotation_y(azimuth)),
+                    ..Default::default()
+                },
+            },
+            Camera3dBundle {
+                transform: camera,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+/// A rig and its camera going into [`compute_rig_delta`]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CameraRigPose {
+    pub rig: Transform,
+    /// Relative to the rig, `None` when the rig has no camera
+    pub camera: Option<Transform>,
+    /// Where they are already heading, see [`CameraRigTarget`]
+    pub rig_target: Option<Transform>,
+    pub camera_target: Option<Transform>,
+    /// Picked when the mouse button went down, see
+    /// [`CameraRigTarget::mouse_gesture`]. `None` works it out from the
+    /// buttons and keys held now
+    pub mouse_gesture: Option<MouseGesture>,
+}
+
+/// Input for one update of a rig, see [`compute_rig_delta`]
+pub struct CameraRigInput<'a> {
+    /// The configs in effect, usually from [`CameraInputContexts`]
+    pub keyboard: &'a KeyboardConf,
+    pub mouse: &'a MouseConf,
+    pub keys: &'a Input<KeyCode>,
+    pub buttons: &'a Input<MouseButton>,
+    pub delta_seconds: f32,
+    /// Scales keyboard, drag and edge scroll panning, see
+    /// [`CameraRigSpeedMultiplier`]
+    pub speed_multiplier: f32,
+    /// Pixels the mouse moved this update
+    pub mouse_motion: Vec2,
+    /// The part of `mouse_motion` after a drag passed
+    /// [`MouseConf::drag_threshold`]
+    pub drag_motion: Vec2,
+    /// Lines scrolled, one per wheel event
+    pub mouse_wheel: &'a [f32],
+    pub window_size: Option<Vec2>,
+    /// Height of the [`GroundPlane`]
+    pub ground: f32,
+    pub cursor: CursorState,
+}
+
+impl<'a> CameraRigInput<'a> {
+    /// Input for a 60th of a second with nothing moved or scrolled, no window
+    /// and the ground at 0, to fill in the rest of a struct literal
+    pub fn new(
+        keyboard: &'a KeyboardConf,
+        mouse: &'a MouseConf,
+        keys: &'a Input<KeyCode>,
+        buttons: &'a Input<MouseButton>,
+    ) -> Self {
+        CameraRigInput {
+            keyboard,
+            mouse,
+            keys,
+            buttons,
+            delta_seconds: 1. / 60.,
+            speed_multiplier: 1.,
+            mouse_motion: Vec2::ZERO,
+            drag_motion: Vec2::ZERO,
+            mouse_wheel: &[],
+            window_size: None,
+            ground: 0.,
+            cursor: CursorState::default(),
+        }
+    }
+}
+
+/// Where the cursor is for one update of a rig, so everything that uses it
+/// agrees on whether it can. While the cursor is off the window, such as
+/// after dragging out of it with a button held, dragging and rotating carry
+/// on with the raw mouse motion, zooming to the cursor zooms to the center of
+/// the view and edge scrolling stops
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tests::{
+/// #     compute_rig_delta, CameraRig, CameraRigInput, CameraRigPose, CursorState,
+/// #     EdgeScrollConf, MouseConf, ZoomAnchor,
+/// # };
+/// let rig = CameraRig {
+///     mouse: MouseConf::default().with_zoom_anchor(ZoomAnchor::Cursor),
+///     edge_scroll: Some(EdgeScrollConf::default()),
+///     ..Default::default()
+/// };
+/// let pose = CameraRigPose {
+///     camera: Some(Transform::from_xyz(-10., 10., 0.).looking_at(Vec3::ZERO, Vec3::Y)),
+///     ..Default::default()
+/// };
+/// let mut buttons = Input::default();
+/// buttons.press(MouseButton::Left);
+/// let rig_after = |cursor: CursorState, drag_motion: Vec2, mouse_wheel: &[f32]| {
+///     let keys = Input::default();
+///     let input = CameraRigInput {
+///         mouse_motion: drag_motion,
+///         drag_motion,
+///         mouse_wheel,
+///         window_size: Some(Vec2::new(800., 600.)),
+///         cursor,
+///         ..CameraRigInput::new(&rig.keyboard, &rig.mouse, &keys, &buttons)
+///     };
+///     compute_rig_delta(&rig, &pose, &input).0.translation
+/// };
+///
+/// // Dragging out of the window, the drag keeps going but the cursor's old
+/// // ground position is ignored
+/// let at_edge = CursorState::new(Some(Vec2::new(1., 300.)), Some(Vec3::new(5., 0., 5.)));
+/// let left = CursorState::new(None, Some(Vec3::new(5., 0., 5.)));
+/// assert!(!left.is_usable());
+/// assert_eq!(left.ground(), None);
+/// assert_ne!(rig_after(left, Vec2::new(-20., 0.), &[]), Vec3::ZERO);
+///
+/// // Edge scrolling stops
+/// assert_ne!(rig_after(at_edge, Vec2::ZERO, &[]), Vec3::ZERO);
+/// assert_eq!(rig_after(left, Vec2::ZERO, &[]), Vec3::ZERO);
+///
+/// // Zooming goes to the center of the view rather than the cursor
+/// let inside = CursorState::new(Some(Vec2::new(400., 300.)), Some(Vec3::new(5., 0., 5.)));
+/// assert_ne!(rig_after(inside, Vec2::ZERO, &[1.]), Vec3::ZERO);
+/// assert_eq!(rig_after(left, Vec2::ZERO, &[1.]), Vec3::ZERO);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CursorState {
+    position: Option<Vec2>,
+    ground: Option<Vec3>,
+}
+
+impl CursorState {
+    /// `position` is in the window with a bottom left origin, as
+    /// `Window::cursor_position`, and `None` when the cursor is off it.
+    /// `ground` is the point on the [`GroundPlane`] under it, see
+    /// [`CursorGroundPosition`], and is dropped when the cursor is off the
+    /// window
+    pub fn new(position: Option<Vec2>, ground: Option<Vec3>) -> Self {
+        CursorState {
+            position,
+            ground: ground.filter(|_| position.is_some()),
+        }
+    }
+
+    /// Whether the cursor is on the window
+    pub fn is_usable(&self) -> bool {
+        self.position.is_some()
+    }
+
+    pub fn position(&self) -> Option<Vec2> {
+        self.position
+    }
+
+    pub fn ground(&self) -> Option<Vec3> {
+        self.ground
+    }
+}
+
+/// Rig and camera transforms `input` moves the rig towards, without touching
+/// the rig itself. This is the math [`CameraRig`]s are moved with, so it can
+/// preview where a rig will end up. [`CameraMode::FreeFly`] moves the camera
+/// directly and isn't handled here
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tests::{compute_rig_delta, CameraRig, CameraRigInput, CameraRigPose};
+/// let rig = CameraRig::default();
+/// let camera = Transform::from_xyz(-75., 75., 0.).looking_at(Vec3::ZERO, Vec3::Y);
+/// let pose = CameraRigPose {
+///     camera: Some(camera),
+///     ..Default::default()
+/// };
+/// let mut keys = Input::default();
+/// keys.press(KeyCode::W);
+/// let buttons = Input::default();
+/// let input = CameraRigInput::new(&rig.keyboard, &rig.mouse, &keys, &buttons);
+///
+/// // Forward is up the screen, which is +X for this camera
+/// let (rig_target, camera_target) = compute_rig_delta(&rig, &pose, &input);
+/// assert!(rig_target.translation.x > 0.);
+/// assert_eq!(rig_target.translation.z, 0.);
+/// assert_eq!(camera_target, camera);
+/// ```
+pub fn compute_rig_delta(
+    rig: &CameraRig,
+    pose: &CameraRigPose,
+    input: &CameraRigInput,
+) -> (Transform, Transform) {
+    let (move_to_rig, move_to_camera, _) = rig_delta(rig, pose, input);
+    (move_to_rig, move_to_camera)
+}
+
+/// [`compute_rig_delta`], also returning whether the rig was panned
+fn rig_delta(
+    rig: &CameraRig,
+    pose: &CameraRigPose,
+    input: &CameraRigInput,
+) -> (Transform, Transform, bool) {
+    let keyboard = input.keyboard;
+    let mouse = input.mouse;
+    let keyboard_input = input.keys;
+    let mouse_input = input.buttons;
+    let rig_transform = pose.rig;
+    let camera_transform = pose.camera;
+
+    let mut move_to_rig = pose.rig_target.unwrap_or(rig_transform);
+
+    // Falls back to the rig origin when the ground isn't hit. The camera's
+    // GlobalTransform isn't propagated yet, so work from the local ones
+    let rotate_pivot = match mouse.orbit_pivot {
+        OrbitPivot::RigOrigin => None,
+        OrbitPivot::ScreenCenterGround => camera_transform.and_then(|camera| {
+            let camera = rig_transform.mul_transform(camera);
+            ground_intersection(camera.translation, camera.forward(), input.ground)
+        }),
+        OrbitPivot::CursorGround => input.cursor.ground(),
+    };
+    let rotation_locked =
+        rig.pixel_snap.is_some() && rig.pixel_snap_rotation == PixelSnapRotation::Locked;
+    let rotate_rig = |transform: &mut Transform, angle: f32| {
+        if rotation_locked {
+            return;
+        }
+        let pivot = rotate_pivot.unwrap_or(transform.translation);
+        transform.rotate_around(pivot, Quat::from_rotation_y(angle));
+    };
+
+    let orbit_conf = if let CameraMode::Orbit(orbit) = rig.camera_mode {
+        Some(orbit)
+    } else {
+        None
+    };
+    let mut orbit_polar = 0.;
+
+    let mut translated = false;
+    // Screen up and right along the ground, so movement follows the view
+    // however the camera was authored
+    let screen_axes = camera_transform.and_then(|camera| {
+        let rotation = rig_transform.rotation * camera.rotation;
+        let up = (rotation * Vec3::Y).reject_from(Vec3::Y).try_normalize()?;
+        let right = (rotation * Vec3::X).reject_from(Vec3::Y).try_normalize()?;
+        Some((up, right))
+    });
+    let rig_axes = |(forward, right): (Vec3, Vec3)| {
+        (
+            rig_transform.rotation * forward,
+            rig_transform.rotation * right,
+        )
+    };
+    // Keep panning on the ground at the same speed, unless it would leave
+    // nothing to pan along
+    let level = |(forward, right): (Vec3, Vec3)| {
+        let level = |direction: Vec3| {
+            direction
+                .reject_from(Vec3::Y)
+                .try_normalize()
+                .map_or(direction, |level| level * direction.length())
+        };
+        if rig.pan_ignores_pitch {
+            (level(forward), level(right))
+        } else {
+            (forward, right)
+        }
+    };
+    let (forward, right) = level(
+        rig.map_directions()
+            .map(rig_axes)
+            .or(screen_axes)
+            .unwrap_or_else(|| rig_axes((Vec3::X, Vec3::Z))),
+    );
+    let mut move_sensitivity = keyboard
+        .move_speed(camera_transform.map_or(0., |camera| camera.translation.length()))
+        * input.speed_multiplier;
+    if keyboard
+        .fine_move_modifier
+        .iter()
+        .any(|key| keyboard_input.pressed(*key))
+    {
+        move_sensitivity *= keyboard.fine_move_multiplier;
+    } else if keyboard
+        .sprint_modifier
+        .iter()
+        .any(|key| keyboard_input.pressed(*key))
+    {
+        move_sensitivity *= keyboard.sprint_multiplier;
+    }
+    // Rig Keyboard Movement
+    if orbit_conf.is_some() {
+        let speed = keyboard.rotate_sensitivity * input.delta_seconds;
+        if keyboard.allow_pan
+            && keyboard
+                .forward
+                .iter()
+                .any(|key| keyboard_input.pressed(*key))
+        {
+            orbit_polar -= speed;
+        }
+        if keyboard.allow_pan
+            && keyboard
+                .backward
+                .iter()
+                .any(|key| keyboard_input.pressed(*key))
+        {
+            orbit_polar += speed;
+        }
+        if keyboard.allow_rotate
+            && keyboard
+                .right
+                .iter()
+                .any(|key| keyboard_input.pressed(*key))
+        {
+            rotate_rig(&mut move_to_rig, speed);
+        }
+        if keyboard.allow_rotate && keyboard.left.iter().any(|key| keyboard_input.pressed(*key)) {
+            rotate_rig(&mut move_to_rig, -speed);
+        }
+    } else {
+        let (forward, right) = if keyboard.world_aligned {
+            level(rig.map_directions().unwrap_or((Vec3::X, Vec3::Z)))
+        } else {
+            (forward, right)
+        };
+        let mut pan = Vec2::ZERO;
+        let mut panned = false;
+        for (keys, direction, allowed) in [
+            (&keyboard.forward, Vec2::Y, keyboard.allow_pan),
+            (&keyboard.backward, Vec2::NEG_Y, keyboard.allow_pan),
+            (&keyboard.right, Vec2::X, keyboard.allow_strafe),
+            (&keyboard.left, Vec2::NEG_X, keyboard.allow_strafe),
+        ] {
+            if allowed && keys.iter().any(|key| keyboard_input.pressed(*key)) {
+                pan += direction;
+                panned = true;
+            }
+        }
+        if panned {
+            let step = apply_pan(Vec3::ZERO, (forward, right), pan, move_sensitivity);
+            move_to_rig.translation += cap_speed(step, keyboard.max_pan_speed, input.delta_seconds);
+            translated = true;
+        }
+    }
+
+    // Rig Edge Scroll
+    if let Some(edge_scroll) = rig.edge_scroll.as_ref().filter(|_| orbit_conf.is_none()) {
+        if let Some((position, size)) = input.cursor.position().zip(input.window_size) {
+            let push = edge_scroll.push(position, size);
+            if push != Vec2::ZERO {
+                let (m, c) = edge_scroll.sensitivity;
+                let distance = camera_transform.map_or(0., |camera| camera.translation.length());
+                let sensitivity = (distance * m + c) * input.speed_multiplier;
+                let step = apply_pan(Vec3::ZERO, (forward, right), push, sensitivity);
+                move_to_rig.translation +=
+                    cap_speed(step, keyboard.max_pan_speed, input.delta_seconds);
+                translated = true;
+            }
+        }
+    }
+
+    // Rig Keyboard Rotation
+    let keyboard_rotate_sensitivity = match (keyboard.rotate_distance_scale, camera_transform) {
+        (Some((m, c)), Some(camera)) => {
+            keyboard.rotate_sensitivity * (camera.translation.length() * m + c).max(0.)
+        }
+        _ => keyboard.rotate_sensitivity,
+    };
+    let mut keyboard_rotation = 0.;
+    if keyboard.allow_rotate
+        && keyboard
+            .counter_clockwise
+            .iter()
+            .any(|key| keyboard_input.pressed(*key))
+    {
+        keyboard_rotation += keyboard_rotate_sensitivity * input.delta_seconds;
+    }
+    if keyboard.allow_rotate
+        && keyboard
+            .clockwise
+            .iter()
+            .any(|key| keyboard_input.pressed(*key))
+    {
+        keyboard_rotation -= keyboard_rotate_sensitivity * input.delta_seconds;
+    }
+    if keyboard_rotation != 0. {
+        // Don't let the target wind up too far ahead of the rig
+        let lead = yaw_between(rig_transform.rotation, move_to_rig.rotation);
+        let max_lead = keyboard.rotate_max_lead;
+        let limited = (lead + keyboard_rotation).clamp(-max_lead, max_lead) - lead;
+        if keyboard_rotation > 0. && limited > 0. {
+            rotate_rig(&mut move_to_rig, limited.min(keyboard_rotation));
+        } else if keyboard_rotation < 0. && limited < 0. {
+            rotate_rig(&mut move_to_rig, limited.max(keyboard_rotation));
+        }
+    }
+
+    // Rig Mouse Motion
+    let rotate_sensitivity = match (mouse.rotate_distance_scale, camera_transform) {
+        (Some((m, c)), Some(camera)) => {
+            mouse.rotate_sensitivity * (camera.translation.length() * m + c).max(0.)
+        }
+        _ => mouse.rotate_sensitivity,
+    };
+    let viewport_scale = input
+        .window_size
+        .filter(|_| mouse.normalize_to_viewport)
+        .map_or(1., |size| 1. / size.y);
+    let mouse_delta = input.mouse_motion * viewport_scale;
+    let drag_delta = input.drag_motion * viewport_scale;
+    let mut mouse_delta_y = 0.;
+    let gesture = pose
+        .mouse_gesture
+        .or_else(|| mouse.gesture(mouse_input, keyboard_input));
+    if gesture == Some(MouseGesture::Rotate) {
+        rotate_rig(&mut move_to_rig, -rotate_sensitivity * mouse_delta.x);
+        mouse_delta_y = mouse_delta.y;
+    } else if mouse
+        .pitch_only_button
+        .is_some_and(|button| mouse_input.pressed(button))
+    {
+        mouse_delta_y = mouse_delta.y;
+    }
+    if drag_delta != Vec2::ZERO && orbit_conf.is_none() {
+        let drag_axes = if mouse.legacy_drag_mapping {
+            rig_axes(rig.map_directions().unwrap_or((Vec3::X, Vec3::Z)))
+        } else {
+            (forward, right)
+        };
+        let drag_sensitivity = (rig_transform.translation.y * mouse.drag_sensitivity.0
+            + mouse.drag_sensitivity.1)
+            * input.speed_multiplier;
+        let step = apply_pan(
+            Vec3::ZERO,
+            drag_axes,
+            Vec2::new(-drag_delta.x, drag_delta.y),
+            drag_sensitivity,
+        );
+        move_to_rig.translation += cap_speed(step, mouse.max_drag_speed, input.delta_seconds);
+        translated = true;
+    }
+
+    let mut move_to_camera = pose.camera_target.or(camera_transform).unwrap_or_default();
+
+    let zoom_from_offset = move_to_camera.translation;
+    let zoom_from = zoom_from_offset.length();
+    let zoom_anchor = match mouse.zoom_anchor {
+        _ if input.mouse_wheel.is_empty() || mouse.fov_zoom.is_some() => None,
+        ZoomAnchor::ViewAxis => None,
+        ZoomAnchor::GroundCenter => {
+            let camera = move_to_rig.mul_transform(move_to_camera);
+            ground_intersection(camera.translation, camera.forward(), input.ground)
+        }
+        ZoomAnchor::Cursor => input.cursor.ground(),
+    };
+
+    // Camera Mouse Zoom
+    for scroll in input.mouse_wheel {
+        if mouse.fov_zoom.is_some() {
+            break;
+        }
+        let scroll = if mouse.zoom_invert_wheel {
+            -scroll
+        } else {
+            *scroll
+        };
+        if let Some(steps) = mouse.zoom_steps.as_deref() {
+            let distance = zoom_step(steps, move_to_camera.translation.length(), scroll);
+            move_to_camera.translation = move_to_camera.translation.normalize_or_zero() * distance;
+            continue;
+        }
+
+        let previous = move_to_camera.translation;
+        let over_zoom = mouse.zoom_limits.zip(mouse.over_zoom_tilt);
+        // Only move along the offset so zooming never changes pitch or yaw
+        move_to_camera.translation = if over_zoom.is_some() {
+            // Zooming through the rig tilts instead, worked out below
+            previous - previous * scroll * mouse.zoom_sensitivity
+        } else {
+            dolly(previous, scroll, mouse.zoom_sensitivity, mouse.zoom_limits)
+        };
+
+        if let Some(((min, max), (min_pitch, max_pitch))) = over_zoom {
+            let previous_distance = previous.length();
+            let mut distance = move_to_camera.translation.length();
+            if move_to_camera.translation.dot(previous) <= 0. {
+                distance = -distance;
+            }
+
+            let pitch = camera_pitch(previous);
+            let tilt_per_distance = (max_pitch - min_pitch) / min.max(0.001);
+            if distance < min {
+                let target = (pitch + (min.min(previous_distance) - distance) * tilt_per_distance)
+                    .min(max_pitch);
+                if target > pitch {
+                    pitch_camera(&mut move_to_camera, target - pitch);
+                }
+            } else if distance > previous_distance && pitch > min_pitch {
+                let target =
+                    (pitch - (distance - previous_distance) * tilt_per_distance).max(min_pitch);
+                pitch_camera(&mut move_to_camera, target - pitch);
+                distance = previous_distance;
+            }
+
+            move_to_camera.translation =
+                move_to_camera.translation.normalize_or_zero() * distance.clamp(min, max);
+            if move_to_camera.translation == Vec3::ZERO {
+                move_to_camera.translation = previous;
+            }
+        }
+    }
+
+    // Keeping the camera on the line through the anchor keeps the anchor in
+    // place on screen. Working out where that line is at the zoomed camera's
+    // height only moves the rig over the ground, so its height stays put
+    if let (Some(anchor), true) = (zoom_anchor, zoom_from > 0.) {
+        let camera_from = move_to_rig.translation + move_to_rig.rotation * zoom_from_offset;
+        let offset = move_to_rig.rotation * move_to_camera.translation;
+        let height = camera_from.y - anchor.y;
+        if height.abs() > f32::EPSILON {
+            let along = (move_to_rig.translation.y + offset.y - anchor.y) / height;
+            let camera_to = anchor + (camera_from - anchor) * along;
+            let correction = camera_to - move_to_rig.translation - offset;
+            move_to_rig.translation.x += correction.x;
+            move_to_rig.translation.z += correction.z;
+        }
+    }
+
+    // Camera Zoom Pitch
+    if let Some(zoom_pitch) = &mouse.zoom_pitch {
+        let distance = move_to_camera.translation.length();
+        if zoom_from > 0. && distance != zoom_from {
+            let pitch = camera_pitch(move_to_camera.translation);
+            let target = if zoom_pitch.snap_back {
+                zoom_pitch.pitch_at(distance)
+            } else {
+                // Change by as much as the curve does, keeping any pitch from
+                // rotating by hand
+                pitch + zoom_pitch.pitch_at(distance) - zoom_pitch.pitch_at(zoom_from)
+            };
+            pitch_camera(&mut move_to_camera, target - pitch);
+        }
+    }
+
+    // Repeatedly composing rotations slowly denormalizes them
+    if !move_to_rig.rotation.is_normalized() {
+        move_to_rig.rotation = move_to_rig.rotation.normalize();
+    }
+    if rig.stabilize_roll && !(move_to_rig.rotation * Vec3::Y).abs_diff_eq(Vec3::Y, 0.00001) {
+        let (yaw, _, _) = move_to_rig.rotation.to_euler(EulerRot::YXZ);
+        move_to_rig.rotation = Quat::from_rotation_y(yaw);
+        if move_to_rig.rotation.dot(rig_transform.rotation) < 0. {
+            move_to_rig.rotation = -move_to_rig.rotation;
+        }
+    }
+
+    if let Some(height) = rig.lock_rig_height {
+        move_to_rig.translation.y = height;
+    }
+    if let Some(floor) = rig.pan_floor {
+        let camera_height = pose
+            .camera_target
+            .or(camera_transform)
+            .map_or(0., |camera| camera.translation.y);
+        let min_height = floor + PAN_FLOOR_CLEARANCE - camera_height;
+        if move_to_rig.translation.y < min_height {
+            move_to_rig.translation.y = min_height;
+        }
+    }
+    // Keeping the view inside the bounds needs the camera's projection, and
+    // springing back needs the rig's momentum, so both are left to
+    // `camera_rig_movement`
+    let visible_area = rig.visible_area_bounds().is_some() && camera_transform.is_some();
+    if let (Some(bounds), false) = (&rig.bounds, visible_area || rig.spring_return_to_bounds) {
+        let position = Vec2::new(move_to_rig.translation.x, move_to_rig.translation.z);
+        let clamped = bounds.clamp(position);
+        move_to_rig.translation.x = clamped.x;
+        move_to_rig.translation.z = clamped.y;
+    }
+
+    // Camera Mouse Rotate
+    if mouse_delta_y != 0. {
+        move_to_camera = orbit(move_to_camera, 0., rotate_sensitivity * mouse_delta_y, None);
+    }
+
+    if let Some(orbit_conf) = orbit_conf {
+        // Polar angles are measured down from straight up
+        move_to_camera = orbit(
+            move_to_camera,
+            0.,
+            -orbit_polar,
+            Some((
+                std::f32::consts::FRAC_PI_2 - orbit_conf.max_polar,
+                std::f32::consts::FRAC_PI_2 - orbit_conf.min_polar,
+            )),
+        );
+        move_to_camera.translation = move_to_camera
+            .translation
+            .clamp_length(orbit_conf.min_radius, orbit_conf.max_radius);
+    }
+
+    if rig.pan_floor.is_some() {
+        let pitch = camera_pitch(move_to_camera.translation);
+        if pitch < PAN_FLOOR_MIN_PITCH {
+            pitch_camera(&mut move_to_camera, PAN_FLOOR_MIN_PITCH - pitch);
+        }
+    }
+
+    if let Some(min_height) = rig.min_camera_height {
+        lift_camera(
+            &mut move_to_camera,
+            input.ground + min_height - move_to_rig.translation.y,
+        );
+    }
+
+    if !move_to_camera.rotation.is_normalized() {
+        move_to_camera.rotation = move_to_camera.rotation.normalize();
+    }
+    if rig.stabilize_roll {
+        remove_roll(&mut move_to_camera);
+    }
+
+    (move_to_rig, move_to_camera, translated)
+}
+
+fn camera_settings_changed(
+    mut settings_events: EventReader<CameraSettingsChanged>,
+    settings: Option<Res<DefaultCameraSettings>>,
+    active_rig: Res<ActiveCameraRig>,
+    mut rig_query: Query<&mut CameraRig>,
+) {
+    let sent = settings_events.iter().count() > 0;
+    let settings = match settings {
+        Some(settings) if sent || (settings.is_changed() && !settings.is_added()) => settings,
+        _ => return,
+    };
+    let rig = match active_rig.0 {
+        Some(entity) => rig_query.get_mut(entity).ok(),
+        None => rig_query.iter_mut().next(),
+    };
+    if let Some(mut rig) = rig {
+        rig.apply_settings(&settings.0);
+    }
+}
+
+fn camera_rig_warmup(mut rig_query: Query<(Ref<CameraRig>, &mut CameraRigTarget)>) {
+    for (rig, mut target) in rig_query.iter_mut() {
+        if rig.is_added() {
+            target.warmup_frames = rig.spawn_warmup_frames;
+        }
+        if target.warmup_frames > 0 {
+            target.warmup_frames -= 1;
+            target.snap_next_update = true;
+        }
+    }
+}
+
+/// Whether the player is moving the rig themselves, through a bound key, a
+/// mouse button held while `mouse_moved`, or the wheel
+fn camera_input_active(
+    camera_mode: CameraMode,
+    keyboard: &KeyboardConf,
+    mouse: &MouseConf,
+    keys: &Input<KeyCode>,
+    buttons: &Input<MouseButton>,
+    mouse_moved: bool,
+    scrolled: bool,
+) -> bool {
+    let key_held = keyboard.bindings().into_iter().any(|(action, keys_bound)| {
+        !matches!(
+            action,
+            ActionName::Sprint | ActionName::FineMove | ActionName::HoldToUnlock
+        ) && keyboard.allows(action, camera_mode)
+            && keys_bound.iter().any(|key| keys.pressed(*key))
+    });
+    let mouse_dragged = mouse_moved
+        && mouse
+            .bindings()
+            .into_iter()
+            .any(|(_, bound)| bound.iter().any(|button| buttons.pressed(*button)));
+    key_held || mouse_dragged || scrolled
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn camera_rig_movement(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    mut mouse_motion_events: EventReader<MouseMotion>,
+    mut mouse_wheel_events: EventReader<MouseWheel>,
+    mut camera_rig_query: Query<(
+        &mut CameraRig,
+        &mut CameraRigTarget,
+        &Children,
+        Entity,
+        Option<&GlobalTransform>,
+    )>,
+    mut rig_cam_query: ParamSet<(
+        Query<&mut Transform, With<CameraRig>>,
+        Query<&mut Transform, (With<Camera>, Without<CameraRigIgnore>)>,
+    )>,
+    mut follow_query: Query<&mut CameraRigFollow>,
+    ground: Res<GroundPlane>,
+    cursor_ground: Res<CursorGroundPosition>,
+    contexts: Res<CameraInputContexts>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    projection_query: Query<&Projection>,
+    rig_cameras: RigCameras,
+    speed_multiplier: Res<CameraRigSpeedMultiplier>,
+) {
+    // Read once so every rig and feature agrees on the cursor this update
+    let window = windows.get_single().ok();
+    let window_size = window.map(|window| Vec2::new(window.width(), window.height()));
+    let cursor = CursorState::new(
+        window.and_then(|window| window.cursor_position()),
+        cursor_ground.0,
+    );
+    let (no_keys, no_buttons) = (Input::default(), Input::default());
+    // Read the mouse once, as every rig gets the same motion and wheel
+    let motion: Vec<Vec2> = mouse_motion_events
+        .iter()
+        .map(|event| event.delta)
+        .collect();
+    let wheel: Vec<f32> = mouse_wheel_events.iter().map(|event| event.y).collect();
+    for (mut rig, mut target, children, entity, rig_global) in camera_rig_query.iter_mut() {
+        if rig.disable {
+            continue;
+        }
+
+        // Either keep the player's hands off an animation or let them take
+        // over from it
+        let animating = target.animating();
+        let blocked = animating && rig.disable_input_during_animation;
+        let (keyboard_input, mouse_input, cursor) = if blocked {
+            (&no_keys, &no_buttons, CursorState::default())
+        } else {
+            (&*keyboard_input, &*mouse_input, cursor)
+        };
+        let edge_scrolling = |rig: &CameraRig| {
+            rig.edge_scroll.as_ref().is_some_and(|edge_scroll| {
+                cursor
+                    .position()
+                    .zip(window_size)
+                    .is_some_and(|(position, size)| edge_scroll.push(position, size) != Vec2::ZERO)
+            })
+        };
+        if animating
+            && !blocked
+            && (camera_input_active(
+                rig.camera_mode,
+                contexts.keyboard(&rig),
+                contexts.mouse(&rig),
+                keyboard_input,
+                mouse_input,
+                !motion.is_empty(),
+                !wheel.is_empty(),
+            ) || edge_scrolling(&rig))
+        {
+            target.focus = None;
+            target.path_playback = None;
+        }
+
+        let camera = rig_cameras.get(&rig, children);
+        let camera_transform =
+            camera.and_then(|camera| rig_cam_query.p1().get(camera).ok().copied());
+        if contexts
+            .keyboard(&rig)
+            .toggle_free_fly
+            .iter()
+            .any(|key| keyboard_input.just_pressed(*key))
+        {
+            if rig.camera_mode == CameraMode::FreeFly {
+                rig.camera_mode = target.free_fly.map_or(CameraMode::Pan, |(mode, _)| mode);
+            } else if let Some(camera_transform) = camera_transform {
+                target.free_fly = Some((rig.camera_mode, camera_transform));
+                rig.camera_mode = CameraMode::FreeFly;
+            }
+        }
+        let rig = &*rig;
+
+        let keyboard = contexts.keyboard(rig);
+        let mouse = contexts.mouse(rig);
+
+        if rig.camera_mode == CameraMode::FreeFly {
+            if let (Some(camera), Some(camera_transform)) = (camera, camera_transform) {
+                if target.free_fly.is_none() {
+                    target.free_fly = Some((CameraMode::Pan, camera_transform));
+                }
+                target.rig = None;
+                target.camera = None;
+                let look: Vec2 = if mouse
+                    .rotate_buttons()
+                    .any(|button| mouse_input.pressed(*button))
+                {
+                    motion.iter().sum()
+                } else {
+                    Vec2::ZERO
+                };
+                // Flying away from the rig doesn't speed up
+                let entry_distance = target
+                    .free_fly
+                    .map_or(0., |(_, camera)| camera.translation.length());
+                if let Ok(mut transform) = rig_cam_query.p1().get_mut(camera) {
+                    free_fly_camera(
+                        &mut transform,
+                        keyboard.move_speed(entry_distance) * speed_multiplier.0,
+                        keyboard,
+                        mouse,
+                        keyboard_input,
+                        look,
+                    );
+                }
+            }
+            continue;
+        }
+
+        let delta = rig.frame_time(&time);
+        if let Some(timer) = target.follow_reactivation_timer.as_mut() {
+            timer.tick(delta);
+        }
+
+        let mut rig_transform = if let Ok(transform) = rig_cam_query.p0().get_mut(entity) {
+            *transform
+        } else {
+            panic!("Rig missing a transform")
+        };
+
+        // Put the rig under where the free camera was looking and ease the
+        // camera back to where it was before flying off
+        if let Some((_, camera_before)) = target.free_fly.take() {
+            if let Some(look_at) = camera_transform.and_then(|camera| {
+                let camera = rig_transform.mul_transform(camera);
+                ground_intersection(camera.translation, camera.forward(), ground.0)
+            }) {
+                let mut move_to_rig = target.rig.unwrap_or(rig_transform);
+                move_to_rig.translation.x = look_at.x;
+                move_to_rig.translation.z = look_at.z;
+                target.rig = Some(move_to_rig);
+            }
+            target.camera = Some(camera_before);
+        }
+
+        // Pick what the mouse does when a button goes down and keep to it
+        // until that button is let go
+        if target
+            .mouse_gesture
+            .is_some_and(|gesture| !mouse.gesture_held(gesture, mouse_input))
+        {
+            target.mouse_gesture = None;
+        }
+        if target.mouse_gesture.is_none() {
+            target.mouse_gesture = mouse.gesture(mouse_input, keyboard_input);
+        }
+        let drag_pressed = target.mouse_gesture == Some(MouseGesture::Drag);
+        if !drag_pressed && (target.dragging || target.drag_distance > 0.) {
+            target.dragging = false;
+            target.drag_distance = 0.;
+        }
+        let mut mouse_motion = Vec2::ZERO;
+        let mut drag_motion = Vec2::ZERO;
+        for delta in &motion {
+            mouse_motion += *delta;
+            if drag_pressed && !target.dragging {
+                target.drag_distance += delta.length();
+                target.dragging = target.drag_distance >= mouse.drag_threshold;
+            }
+            if drag_pressed && target.dragging {
+                drag_motion += *delta;
+            }
+        }
+        let mouse_wheel: Vec<f32> = if blocked {
+            Vec::new()
+        } else if camera.is_some() {
+            wheel.clone()
+        } else {
+            Vec::new()
+        };
+
+        // The movement math works in world units, so take out the scale the
+        // rig has or inherits and put it back afterwards
+        let scale = rig_global
+            .map(|global| global.to_scale_rotation_translation().0)
+            .filter(|scale| scale.abs().min_element() > 0.)
+            .unwrap_or(Vec3::ONE);
+        let parent_scale = scale / rig_transform.scale;
+        let unscaled_rig = |transform: Transform| Transform {
+            scale: Vec3::ONE,
+            ..transform
+        };
+        let unscaled_camera = |transform: Transform| Transform {
+            translation: transform.translation * scale,
+            ..transform
+        };
+        let rig_from = target.rig.unwrap_or(rig_transform);
+        let (mut move_to_rig, mut move_to_camera, translated) = rig_delta(
+            rig,
+            &CameraRigPose {
+                rig: unscaled_rig(rig_transform),
+                camera: camera_transform.map(unscaled_camera),
+                rig_target: target.rig.map(unscaled_rig),
+                camera_target: target.camera.map(unscaled_camera),
+                mouse_gesture: target.mouse_gesture,
+            },
+            &CameraRigInput {
+                delta_seconds: delta.as_secs_f32(),
+                speed_multiplier: speed_multiplier.0,
+                mouse_motion,
+                drag_motion,
+                mouse_wheel: &mouse_wheel,
+                window_size,
+                ground: ground.0,
+                cursor,
+                ..CameraRigInput::new(keyboard, mouse, keyboard_input, mouse_input)
+            },
+        );
+
+        move_to_rig.scale = rig_from.scale;
+        if parent_scale != Vec3::ONE {
+            move_to_rig.translation = rig_from.translation
+                + (move_to_rig.translation - rig_from.translation) / parent_scale;
+        }
+
+        let position = Vec2::new(move_to_rig.translation.x, move_to_rig.translation.z);
+        let clamped = match (rig.visible_area_bounds(), camera) {
+            (Some((rect, clamp_zoom)), Some(camera)) => match projection_query.get(camera) {
+                Ok(projection) => Some(clamp_visible_area(
+                    rect,
+                    clamp_zoom,
+                    &move_to_rig,
+                    &mut move_to_camera,
+                    projection,
+                    rig.ortho_scale_per_distance,
+                    ground.0,
+                )),
+                // Without a `Projection`, such as a 2D camera, there is no
+                // view to keep inside
+                Err(_) => Some(position.clamp(rect.min, rect.max)),
+            },
+            _ => rig
+                .bounds
+                .as_ref()
+                .filter(|_| rig.spring_return_to_bounds)
+                .map(|bounds| bounds.clamp(position)),
+        };
+        if let Some(clamped) = clamped {
+            let returned = rig.return_to_bounds(
+                position,
+                clamped,
+                &mut target.bounds_velocity,
+                delta.as_secs_f32(),
+            );
+            move_to_rig.translation.x = returned.x;
+            move_to_rig.translation.z = returned.y;
+        }
+        move_to_camera.translation /= scale;
+
+        // Panning against a position lock goes nowhere, so shouldn't stop
+        // following either
+        let translated = translated && rig.position_lock.is_none();
+
+        let locked = matches!(rig.follow_dynamics, FollowDynamics::Locked { .. });
+        let unlocked = locked
+            && (keyboard
+                .hold_to_unlock
+                .iter()
+                .any(|key| keyboard_input.pressed(*key))
+                || edge_scrolling(rig));
+        if target.follow_unlocked != unlocked {
+            target.follow_unlocked = unlocked;
+        }
+
+        if translated && !locked {
+            target.follow_blend = 0.;
+            target.follow_velocity = Vec3::ZERO;
+            if rig.follow_activation_delay_secs > 0. {
+                target.follow_reactivation_timer = Some(Timer::from_seconds(
+                    rig.follow_activation_delay_secs,
+                    TimerMode::Once,
+                ));
+            } else {
+                for mut followable in follow_query.iter_mut() {
+                    followable.active = false;
+                }
+            }
+        }
+
+        // A new target starts a new move along the smoothing curve
+        if target.rig.unwrap_or(rig_transform) != move_to_rig
+            || target.camera.or(camera_transform) != camera_transform.and(Some(move_to_camera))
+        {
+            target.smoothing_progress = 0.;
+        }
+        target.rig = Some(move_to_rig);
+
+        let snap = target.snap_next_update;
+        if snap {
+            rig_transform = move_to_rig;
+        }
+
+        // Anything else moving the rig takes over from zooming towards an
+        // anchor
+        if translated
+            || snap
+            || target
+                .zoom_focus
+                .is_some_and(|(_, rig_to, _)| rig_to != move_to_rig.translation)
+        {
+            target.zoom_focus = None;
+        }
+        if !mouse_wheel.is_empty()
+            && mouse.zoom_anchor != ZoomAnchor::ViewAxis
+            && mouse.fov_zoom.is_none()
+            && !translated
+            && !snap
+        {
+            target.zoom_focus = camera_transform.map(|camera| {
+                (
+                    rig_transform.translation,
+                    move_to_rig.translation,
+                    camera.translation.length(),
+                )
+            });
+        }
+
+        let smoothing = (delta.as_secs_f32() / SMOOTHING_SECS).min(1.);
+        // Linear progress through the move is eased along the curve, then
+        // turned back into a fraction of what is left of each gap
+        let progress = target.smoothing_progress;
+        let curved = |factor: f32| {
+            let from = apply_smoothstep(&rig.smooth_step_mode, progress);
+            let to = progress + (1. - progress) * factor;
+            if to >= 1. || from >= 1. {
+                1.
+            } else {
+                ((apply_smoothstep(&rig.smooth_step_mode, to) - from) / (1. - from)).clamp(0., 1.)
+            }
+        };
+        target.smoothing_progress = progress + (1. - progress) * smoothing;
+        let steps = rig.sync_smoothing.then(|| {
+            let fov_gap = camera
+                .and_then(|camera| match projection_query.get(camera) {
+                    Ok(Projection::Perspective(perspective)) => Some(perspective.fov),
+                    _ => None,
+                })
+                .zip(target.fov)
+                .map_or(0., |(fov, target_fov)| (target_fov - fov).abs());
+            let camera_gaps = camera_transform.map_or((0., 0.), |camera| {
+                (
+                    move_to_camera.translation.distance(camera.translation),
+                    rotation_gap(move_to_camera.rotation, camera.rotation),
+                )
+            });
+            settle_steps(
+                &[
+                    (
+                        move_to_rig.translation.distance(rig_transform.translation),
+                        0.005,
+                    ),
+                    (
+                        rotation_gap(move_to_rig.rotation, rig_transform.rotation),
+                        0.00001,
+                    ),
+                    (camera_gaps.0, 0.005),
+                    (camera_gaps.1, 0.00001),
+                    (fov_gap, 0.0001),
+                ],
+                smoothing,
+            )
+        });
+        target.smoothing_steps = steps;
+        // Fraction of a gap to close this update, `None` to snap it shut
+        let ease = |gap: f32, snap: f32| match steps {
+            Some(steps) => (steps > 0.).then(|| curved(settle_factor(gap, snap, steps, smoothing))),
+            None => (gap > snap).then(|| curved(smoothing)),
+        };
+
+        // Smoothly move the rig
+        if move_to_rig.translation != rig_transform.translation {
+            if let Some(factor) = ease(
+                move_to_rig.translation.distance(rig_transform.translation),
+                0.005,
+            ) {
+                rig_transform.translation = rig_transform
+                    .translation
+                    .lerp(move_to_rig.translation, factor);
+            } else {
+                rig_transform.translation = move_to_rig.translation;
+            }
+        }
+        if move_to_rig.rotation != rig_transform.rotation {
+            if let Some(factor) = ease(
+                rotation_gap(move_to_rig.rotation, rig_transform.rotation),
+                0.00001,
+            ) {
+                rig_transform.rotation = rig_transform.rotation.lerp(move_to_rig.rotation, factor);
+            } else {
+                rig_transform.rotation = move_to_rig.rotation;
+            }
+        }
+        let mut cameras = rig_cam_query.p1();
+        if let Some(mut transform) = camera.and_then(|camera| cameras.get_mut(camera).ok()) {
+            if mouse.zoom_steps.is_some()
+                && mouse.fov_zoom.is_none()
+                && !rig.zoom_step_animation
+                && !mouse_wheel.is_empty()
+            {
+                transform.translation = move_to_camera.translation;
+            }
+
+            target.camera = Some(move_to_camera);
+
+            if snap && *transform != move_to_camera {
+                *transform = move_to_camera;
+            }
+
+            // Smoothly move the camera
+            if move_to_camera.translation != transform.translation {
+                if let Some(factor) = ease(
+                    move_to_camera.translation.distance(transform.translation),
+                    0.005,
+                ) {
+                    let translation = transform
+                        .translation
+                        .lerp(move_to_camera.translation, factor);
+                    transform.translation = match rig.zoom_smoothing {
+                        Some(zoom_smoothing) if steps.is_none() => {
+                            // Keep the direction eased as above, only
+                            // easing the distance at its own rate
+                            let distance = transform.translation.length();
+                            let target_distance = move_to_camera.translation.length();
+                            let distance = if (target_distance - distance).abs() > 0.005 {
+                                distance
+                                    + (target_distance - distance)
+                                        * (zoom_smoothing * delta.as_secs_f32()).min(1.)
+                            } else {
+                                target_distance
+                            };
+                            translation
+                                .try_normalize()
+                                .map_or(translation, |direction| direction * distance)
+                        }
+                        _ => translation,
+                    };
+                } else {
+                    transform.translation = move_to_camera.translation;
+                }
+            }
+            if move_to_camera.rotation != transform.rotation {
+                if let Some(factor) = ease(
+                    rotation_gap(move_to_camera.rotation, transform.rotation),
+                    0.00001,
+                ) {
+                    transform.rotation = transform.rotation.lerp(move_to_camera.rotation, factor);
+                } else {
+                    transform.rotation = move_to_camera.rotation;
+                }
+            }
+            if rig.stabilize_roll {
+                let mut levelled = *transform;
+                if remove_roll(&mut levelled) {
+                    *transform = levelled;
+                }
+            }
+            if move_to_camera == *transform {
+                target.camera = None;
+            }
+
+            if let Some((rig_from, rig_to, distance_from)) = target.zoom_focus {
+                let distance_to = move_to_camera.translation.length();
+                // Nothing to keep pace with when the zoom was already at its
+                // limit, the rig eases as usual
+                let progress = (distance_to != distance_from).then(|| {
+                    ((distance_from - transform.translation.length())
+                        / (distance_from - distance_to))
+                        .clamp(0., 1.)
+                });
+                if let Some(progress) = progress {
+                    rig_transform.translation = rig_from.lerp(rig_to, progress);
+                }
+                if progress.is_none() || progress == Some(1.) {
+                    target.zoom_focus = None;
+                }
+            }
+        }
+
+        if move_to_rig == rig_transform {
+            target.rig = None;
+        }
+
+        if let Ok(mut transform) = rig_cam_query.p0().get_mut(entity) {
+            if *transform != rig_transform {
+                *transform = rig_transform;
+            }
+        }
+
+        if snap {
+            target.snap_next_update = false;
+            // Pick following straight back up too
+            target.follow_reactivation_timer = None;
+            target.follow_blend = 1.;
+        }
+    }
+}
+
+/// Spawns a camera behind new rigs that don't have one, at the same offset as
+/// the demo
+#[allow(clippy::type_complexity)]
+fn camera_rig_auto_camera(
+    mut commands: Commands,
+    rig_query: Query<(Entity, &CameraRig, Option<&Children>), Added<CameraRig>>,
+    rig_cameras: RigCameras,
+) {
+    for (entity, rig, children) in rig_query.iter() {
+        if children.is_some_and(|children| rig_cameras.get(rig, children).is_some()) {
+            continue;
+        }
+        commands.entity(entity).with_children(|parent| {
+            let mut camera = parent.spawn((
+                Camera3dBundle {
+                    transform: Transform::from_xyz(-75., 75., 0.).looking_at(Vec3::ZERO, Vec3::Y),
+                    ..Default::default()
+                },
+                RigCamera,
+            ));
+            if let Some(layers) = rig.render_layers {
+                camera.insert(layers);
+            }
+        });
+    }
+}
+
+/// Warns about new rigs with a button set to both rotate and drag, without
+/// modifiers to tell them apart
+fn camera_mouse_conf_warning(rig_query: Query<(Entity, &CameraRig), Added<CameraRig>>) {
+    for (entity, rig) in rig_query.iter() {
+        let mouse = &rig.mouse;
+        let ambiguous = match (&mouse.rotate_modifier, &mouse.drag_modifier) {
+            (None, None) => true,
+            (Some(rotate), Some(drag)) => rotate.iter().any(|key| drag.contains(key)),
+            _ => false,
+        };
+        if !ambiguous {
+            continue;
+        }
+        if mouse.middle_drag_rotates && mouse.drag.contains(&MouseButton::Middle) {
+            warn!(
+                "Camera rig {:?} has middle_drag_rotates set and MouseButton::Middle in drag, \
+                 the middle button will only rotate",
+                entity
+            );
+        } else if let Some(button) = mouse
+            .drag
+            .iter()
+            .find(|button| mouse.rotate.contains(button))
+        {
+            warn!(
+                "Camera rig {:?} has {:?} in both rotate and drag without a rotate_modifier or \
+                 drag_modifier to tell them apart, it will only rotate",
+                entity, button
+            );
+        }
+    }
+}
+
+/// Warns once about each rig that it or its camera is scaled
+#[allow(clippy::type_complexity)]
+fn camera_scale_warning(
+    mut warned: Local<HashSet<Entity>>,
+    mut removed_rigs: RemovedComponents<CameraRig>,
+    rig_query: Query<(Entity, &GlobalTransform, &Children), With<CameraRig>>,
+    camera_query: Query<&Transform, (With<Camera>, Without<CameraRigIgnore>)>,
+) {
+    for entity in removed_rigs.iter() {
+        warned.remove(&entity);
+    }
+    let scaled = |scale: Vec3| !scale.abs_diff_eq(Vec3::ONE, 0.0001);
+    for (entity, global_transform, children) in rig_query.iter() {
+        if warned.contains(&entity) {
+            continue;
+        }
+        let (scale, _, _) = global_transform.to_scale_rotation_translation();
+        if scaled(scale)
+            || children.iter().any(|child| {
+                camera_query
+                    .get(*child)
+                    .is_ok_and(|transform| scaled(transform.scale))
+            })
+        {
+            warn!(
+                "Camera rig {:?} or its camera is scaled. Movement and zooming make up for the \
+                 rig's scale, but bounds and the camera's own scale are left as they are. \
+                 Check the scale of their transforms and the rig's parents",
+                entity
+            );
+            warned.insert(entity);
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn camera_ortho_zoom(
+    rig_query: Query<(&CameraRig, &Children)>,
+    mut camera_query: Query<
+        (&Transform, &mut Projection),
+        (With<Camera>, Without<CameraRigIgnore>),
+    >,
+    rig_cameras: RigCameras,
+) {
+    for (rig, children) in rig_query.iter() {
+        let scale_per_distance = if let Some(scale_per_distance) = rig.ortho_scale_per_distance {
+            scale_per_distance
+        } else {
+            continue;
+        };
+        if let Some(camera) = rig_cameras.get(rig, children) {
+            if let Ok((transform, mut projection)) = camera_query.get_mut(camera) {
+                let scale = transform.translation.length() * scale_per_distance;
+                match &*projection {
+                    Projection::Orthographic(ortho) if ortho.scale != scale => {}
+                    _ => continue,
+                }
+                if let Projection::Orthographic(ortho) = projection.as_mut() {
+                    ortho.scale = scale;
+                }
+            }
+        }
+    }
+}
+
+fn camera_fov_zoom(
+    time: Res<Time>,
+    contexts: Res<CameraInputContexts>,
+    mut mouse_wheel_events: EventReader<MouseWheel>,
+    mut rig_query: Query<(&CameraRig, &mut CameraRigTarget, &Children)>,
+    mut projection_query: Query<&mut Projection, (With<Camera>, Without<CameraRigIgnore>)>,
+    rig_cameras: RigCameras,
+) {
+    let scroll: f32 = mouse_wheel_events.iter().map(|event| event.y).sum();
+    for (rig, mut target, children) in rig_query.iter_mut() {
+        let mouse = contexts.mouse(rig);
+        let delta = rig.frame_time(&time);
+        let fov_zoom = if let Some(fov_zoom) = &mouse.fov_zoom {
+            fov_zoom
+        } else {
+            // Don't resume an old target if fov zoom is turned back on
+            if target.fov.is_some() {
+                target.fov = None;
+            }
+            continue;
+        };
+        let scroll = if rig.disable || (target.animating() && rig.disable_input_during_animation) {
+            0.
+        } else if mouse.zoom_invert_wheel {
+            -scroll
+        } else {
+            scroll
+        };
+
+        if let Some(camera) = rig_cameras.get(rig, children) {
+            if let Ok(mut projection) = projection_query.get_mut(camera) {
+                let fov = if let Projection::Perspective(perspective) = &*projection {
+                    perspective.fov
+                } else {
+                    continue;
+                };
+
+                let target_fov = (target.fov.unwrap_or(fov) - scroll * fov_zoom.sensitivity)
+                    .clamp(fov_zoom.limits.0, fov_zoom.limits.1);
+                if target_fov == fov {
+                    target.fov = None;
+                    continue;
+                }
+                target.fov = Some(target_fov);
+
+                let gap = (target_fov - fov).abs();
+                let factor = if rig.sync_smoothing {
+                    // Also start easing when only the fov is moving
+                    let smoothing = delta.as_secs_f32() / SMOOTHING_SECS;
+                    let steps = settle_steps(&[(gap, 0.0001)], smoothing)
+                        .max(target.smoothing_steps.unwrap_or(0.));
+                    (steps > 0.).then(|| settle_factor(gap, 0.0001, steps, smoothing))
+                } else {
+                    (gap > 0.0001).then(|| (fov_zoom.smoothing * delta.as_secs_f32()).min(1.))
+                };
+                if let Projection::Perspective(perspective) = projection.as_mut() {
+                    if let Some(factor) = factor {
+                        perspective.fov = fov + (target_fov - fov) * factor;
+                    } else {
+                        perspective.fov = target_fov;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Commands for controlling a rig without querying it. Apart from `PanTo`
+/// with a rig set these apply to the [`ActiveCameraRig`], and commands sent in
+/// the same frame are applied in order
+#[derive(Clone, Debug)]
+pub enum CameraCommand {
+    /// `snap` moves there straight away along with anything else in flight,
+    /// see [`CameraRigTarget::snap_next_update`]
+    PanTo {
+        rig: Option<Entity>,
+        target: Vec3,
+        snap: bool,
+    },
+    /// Distance of the camera from the rig
+    ZoomTo {
+        distance: f32,
+        snap: bool,
+    },
+    /// Rotation of the rig around `Vec3::Y` in radians
+    RotateTo {
+        yaw: f32,
+    },
+    Follow(Entity),
+    StopFollow,
+    /// Moves the rig and camera straight to their targets
+    Snap,
+    /// Moves back to the pose the rig was spawned with
+    Reset,
+    /// See [`CameraRigTarget::focus_on_entity`]
+    FocusOn {
+        entity: Entity,
+        distance: f32,
+        duration: f32,
+    },
+    /// See [`CameraRigTarget::stop`], this also ends the camera's
+    /// [`CameraShake`]
+    Stop,
+}
+
+/// Turns the follow keys into [`CameraCommand`]s, so they can be observed like
+/// any other command
+fn camera_follow_keys(
+    keyboard_input: Res<Input<KeyCode>>,
+    contexts: Res<CameraInputContexts>,
+    rig_query: Query<(Entity, &CameraRig)>,
+    follow_query: Query<(Entity, &Transform, &CameraRigFollow)>,
+    mut camera_commands: EventWriter<CameraCommand>,
+) {
+    let (followable, followable_transform, following) =
+        if let Some(followable) = follow_query.iter().last() {
+            followable
+        } else {
+            return;
+        };
+    for (entity, rig) in rig_query.iter() {
+        if rig.disable {
+            continue;
+        }
+        let keyboard = contexts.keyboard(rig);
+        if keyboard
+            .toggle_follow
+            .iter()
+            .any(|key| keyboard_input.just_pressed(*key))
+        {
+            if following.active {
+                camera_commands.send(CameraCommand::StopFollow);
+            } else {
+                camera_commands.send(CameraCommand::Follow(followable));
+                // Following only moves the rig when the target moves, so head
+                // back to it in case it is standing still
+                camera_commands.send(CameraCommand::PanTo {
+                    rig: Some(entity),
+                    target: followable_transform.translation,
+                    snap: false,
+                });
+            }
+        }
+        if keyboard
+            .center_on_target
+            .iter()
+            .any(|key| keyboard_input.just_pressed(*key))
+        {
+            camera_commands.send(CameraCommand::PanTo {
+                rig: Some(entity),
+                target: followable_transform.translation,
+                snap: false,
+            });
+        }
+    }
+}
+
+/// Turns the [`ActiveCameraRig`]'s gamepad button bindings into
+/// [`CameraCommand`]s
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn camera_gamepad_buttons(
+    button_input: Res<Input<GamepadButton>>,
+    active_rig: Res<ActiveCameraRig>,
+    selected: Res<SelectedEntity>,
+    selected_query: Query<(&Transform, Option<&CameraRigFollow>)>,
+    rigs: Query<(Entity, &Children), With<CameraRig>>,
+    rig_query: Query<(&CameraRig, &CameraRigTarget, &Transform)>,
+    camera_query: Query<&Transform, (With<Camera>, Without<CameraRig>, Without<CameraRigIgnore>)>,
+    rig_cameras: RigCameras,
+    mut camera_commands: EventWriter<CameraCommand>,
+) {
+    let just_pressed = |buttons: &[GamepadButtonType]| {
+        button_input
+            .get_just_pressed()
+            .any(|button| buttons.contains(&button.button_type))
+    };
+    let (rig_entity, rig, target, rig_transform, children) =
+        if let Some((entity, children)) = active_rig.get(&rigs) {
+            if let Ok((rig, target, rig_transform)) = rig_query.get(entity) {
+                (entity, rig, target, rig_transform, children)
+            } else {
+                return;
+            }
+        } else {
+            return;
+        };
+    if rig.disable || (target.animating() && rig.disable_input_during_animation) {
+        return;
+    }
+    let camera_distance = rig_cameras
+        .get(rig, children)
+        .and_then(|camera| camera_query.get(camera).ok())
+        .map(|camera| target.camera.unwrap_or(*camera).translation.length());
+    let yaw = yaw_between(
+        Quat::IDENTITY,
+        target.rig.unwrap_or(*rig_transform).rotation,
+    );
+    for (action, buttons) in rig.gamepad.button_bindings.iter() {
+        if !just_pressed(buttons) {
+            continue;
+        }
+        let command = match action {
+            CameraAction::SnapRotateClockwise => CameraCommand::RotateTo {
+                yaw: yaw - std::f32::consts::FRAC_PI_2,
+            },
+            CameraAction::SnapRotateCounterClockwise => CameraCommand::RotateTo {
+                yaw: yaw + std::f32::consts::FRAC_PI_2,
+            },
+            CameraAction::ResetView => CameraCommand::Reset,
+            CameraAction::ToggleFollowSelected => {
+                let (entity, (transform, follow)) = if let Some(selected) = selected
+                    .0
+                    .and_then(|entity| Some((entity, selected_query.get(entity).ok()?)))
+                {
+                    selected
+                } else {
+                    continue;
+                };
+                if follow.is_some_and(|follow| follow.active) {
+                    CameraCommand::StopFollow
+                } else {
+                    camera_commands.send(CameraCommand::Follow(entity));
+                    // As with the follow keys, head to it in case it is
+                    // standing still
+                    CameraCommand::PanTo {
+                        rig: Some(rig_entity),
+                        target: transform.translation,
+                        snap: false,
+                    }
+                }
+            }
+            CameraAction::ZoomIn | CameraAction::ZoomOut => {
+                let distance = if let Some(distance) = camera_distance {
+                    distance
+                } else {
+                    continue;
+                };
+                let zoom_in = (*action == CameraAction::ZoomIn) != rig.gamepad.zoom_invert_gamepad;
+                let mut distance = if let Some(steps) = &rig.mouse.zoom_steps {
+                    zoom_step(steps, distance, if zoom_in { 1. } else { -1. })
+                } else if zoom_in {
+                    distance * rig.gamepad.zoom_step_ratio
+                } else {
+                    distance / rig.gamepad.zoom_step_ratio
+                };
+                if let Some((min, max)) = rig.mouse.zoom_limits {
+                    distance = distance.clamp(min, max);
+                }
+                CameraCommand::ZoomTo {
+                    distance,
+                    snap: false,
+                }
+            }
+        };
+        camera_commands.send(command);
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn camera_commands(
+    mut commands: Commands,
+    mut camera_commands: EventReader<CameraCommand>,
+    active_rig: Res<ActiveCameraRig>,
+    mut rig_query: Query<(Entity, &CameraRig, &mut CameraRigTarget, &Children)>,
+    mut rig_transform_query: Query<&mut Transform, With<CameraRig>>,
+    mut camera_transform_query: Query<
+        &mut Transform,
+        (With<Camera>, Without<CameraRig>, Without<CameraRigIgnore>),
+    >,
+    rig_cameras: RigCameras,
+    mut follow_query: Query<(Entity, &mut CameraRigFollow)>,
+    followable_query: Query<(), With<GlobalTransform>>,
+    mut shake_query: Query<&mut CameraShake>,
+) {
+    for (entity, rig, mut target, children) in rig_query.iter_mut() {
+        if target.home.is_none() {
+            let camera = rig_cameras
+                .get(rig, children)
+                .and_then(|camera| camera_transform_query.get(camera).ok());
+            if let (Ok(rig_transform), Some(camera_transform)) =
+                (rig_transform_query.get(entity), camera)
+            {
+                target.home = Some((
+                    target.rig.unwrap_or(*rig_transform),
+                    target.camera.unwrap_or(*camera_transform),
+                ));
+            }
+        }
+    }
+
+    let default_rig = active_rig
+        .0
+        .or_else(|| rig_query.iter().next().map(|(entity, ..)| entity));
+    for command in camera_commands.iter() {
+        let rig = match command {
+            CameraCommand::PanTo { rig: Some(rig), .. } => *rig,
+            _ => {
+                if let Some(rig) = default_rig {
+                    rig
+                } else {
+                    warn!("No camera rig to apply {:?} to", command);
+                    continue;
+                }
+            }
+        };
+        let (camera_rig, mut target, children) =
+            if let Ok((_, camera_rig, target, children)) = rig_query.get_mut(rig) {
+                (camera_rig, target, children)
+            } else {
+                warn!("{:?} is not a camera rig, ignoring {:?}", rig, command);
+                continue;
+            };
+        let mut rig_transform = if let Ok(transform) = rig_transform_query.get_mut(rig) {
+            transform
+        } else {
+            continue;
+        };
+        let camera = rig_cameras.get(camera_rig, children);
+
+        match command {
+            CameraCommand::PanTo {
+                target: point,
+                snap,
+                ..
+            } => {
+                let mut move_to_rig = target.rig.unwrap_or(*rig_transform);
+                move_to_rig.translation = *point;
+                target.rig = Some(move_to_rig);
+                target.snap_next_update |= *snap;
+            }
+            CameraCommand::ZoomTo { distance, snap } => {
+                target.snap_next_update |= *snap;
+                if let Some(camera_transform) =
+                    camera.and_then(|camera| camera_transform_query.get(camera).ok())
+                {
+                    let mut move_to_camera = target.camera.unwrap_or(*camera_transform);
+                    move_to_camera.translation =
+                        move_to_camera.translation.normalize_or_zero() * *distance;
+                    target.camera = Some(move_to_camera);
+                }
+            }
+            CameraCommand::RotateTo { yaw } => {
+                let mut move_to_rig = target.rig.unwrap_or(*rig_transform);
+                move_to_rig.rotation = Quat::from_rotation_y(*yaw);
+                target.rig = Some(move_to_rig);
+            }
+            CameraCommand::Follow(entity) => {
+                if !followable_query.contains(*entity) {
+                    warn!("Can't follow {:?}, it has no transform", entity);
+                    continue;
+                }
+                let mut found = false;
+                for (followable, mut follow) in follow_query.iter_mut() {
+                    follow.active = followable == *entity;
+                    found |= follow.active;
+                }
+                if !found {
+                    commands.entity(*entity).insert(CameraRigFollow::new(true));
+                }
+                target.follow_reactivation_timer = None;
+                target.follow_blend = 0.;
+            }
+            CameraCommand::StopFollow => {
+                for (_, mut follow) in follow_query.iter_mut() {
+                    follow.active = false;
+                }
+            }
+            CameraCommand::Snap => {
+                if let Some(move_to_rig) = target.rig.take() {
+                    *rig_transform = move_to_rig;
+                }
+                if let Some(move_to_camera) = target.camera.take() {
+                    if let Some(mut camera_transform) =
+                        camera.and_then(|camera| camera_transform_query.get_mut(camera).ok())
+                    {
+                        *camera_transform = move_to_camera;
+                    }
+                }
+            }
+            CameraCommand::Reset => {
+                if let Some((rig_home, camera_home)) = target.home {
+                    target.rig = Some(rig_home);
+                    target.camera = Some(camera_home);
+                }
+            }
+            CameraCommand::FocusOn {
+                entity,
+                distance,
+                duration,
+            } => {
+                target.focus_on_entity(*entity, *distance, *duration);
+            }
+            CameraCommand::Stop => {
+                target.stop();
+                for child in children.iter() {
+                    if let Ok(mut shake) = shake_query.get_mut(*child) {
+                        shake.trauma = 0.;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Hides and locks the cursor while rotating, restoring it when the rotate
+/// button is released or the window loses focus
+fn camera_cursor_grab(
+    contexts: Res<CameraInputContexts>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    mut focus_events: EventReader<WindowFocused>,
+    rig_query: Query<&CameraRig>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    mut grab: Local<Option<(MouseButton, bool, CursorGrabMode)>>,
+) {
+    let mut window = if let Ok(window) = windows.get_single_mut() {
+        window
+    } else {
+        return;
+    };
+
+    // Read every update so old events don't end a later grab, and go by the
+    // last one in case focus came back within the same update
+    let lost_focus = focus_events
+        .iter()
+        .last()
+        .is_some_and(|event| !event.focused);
+    if let Some((button, visible, grab_mode)) = *grab {
+        if lost_focus || !mouse_input.pressed(button) {
+            window.cursor.visible = visible;
+            window.cursor.grab_mode = grab_mode;
+            *grab = None;
+        }
+        return;
+    }
+
+    for rig in rig_query.iter().filter(|rig| !rig.disable) {
+        let mouse = contexts.mouse(rig);
+        let pressed = mouse
+            .rotate_buttons()
+            .find(|button| mouse_input.just_pressed(**button))
+            .filter(|_| mouse.gesture(&mouse_input, &keyboard_input) == Some(MouseGesture::Rotate));
+        if let (Some(button), true) = (
+            pressed,
+            mouse.hide_cursor_during_rotate || mouse.cursor_lock_during_rotate,
+        ) {
+            *grab = Some((*button, window.cursor.visible, window.cursor.grab_mode));
+            if mouse.hide_cursor_during_rotate {
+                window.cursor.visible = false;
+            }
+            if mouse.cursor_lock_during_rotate {
+                window.cursor.grab_mode = CursorGrabMode::Locked;
+            }
+            break;
+        }
+    }
+}
+
+/// Point where a ray hits the ground plane at `height`
+fn ground_intersection(origin: Vec3, direction: Vec3, height: f32) -> Option<Vec3> {
+    let ray = Ray { origin, direction };
+    let distance = ray.intersect_plane(Vec3::Y * height, Vec3::Y)?;
+    Some(ray.get_point(distance))
+}
+
+/// The step after the one nearest to `distance`, towards the smaller steps
+/// when scrolling up
+fn zoom_step(steps: &[f32], distance: f32, scroll: f32) -> f32 {
+    let nearest = if let Some(nearest) = (0..steps.len()).min_by(|a, b| {
+        (steps[*a] - distance)
+            .abs()
+            .total_cmp(&(steps[*b] - distance).abs())
+    }) {
+        nearest
+    } else {
+        return distance;
+    };
+    let step = if scroll > 0. {
+        nearest.saturating_sub(1)
+    } else if scroll < 0. {
+        (nearest + 1).min(steps.len() - 1)
+    } else {
+        nearest
+    };
+    steps[step]
+}
+
+/// Signed rotation around `Vec3::Y` from `from` to `to`, in `[-PI, PI]`
+fn yaw_between(from: Quat, to: Quat) -> f32 {
+    let (axis, angle) = (from.inverse() * to).to_axis_angle();
+    let angle = if axis.y < 0. { -angle } else { angle };
+    if angle > std::f32::consts::PI {
+        angle - std::f32::consts::TAU
+    } else if angle < -std::f32::consts::PI {
+        angle + std::f32::consts::TAU
+    } else {
+        angle
+    }
+}
+
+/// Raises the camera to at least `min_y` above the rig, pitching it up around
+/// the rig and only moving it further out past [`LIFT_MAX_PITCH`]
+fn lift_camera(camera: &mut Transform, min_y: f32) {
+    if camera.translation.y >= min_y {
+        return;
+    }
+    let distance = camera.translation.length();
+    let pitch = camera_pitch(camera.translation);
+    if min_y < distance * LIFT_MAX_PITCH.sin() {
+        pitch_camera(camera, (min_y / distance).asin() - pitch);
+    } else {
+        pitch_camera(camera, LIFT_MAX_PITCH - pitch);
+        if camera.translation.y > f32::EPSILON {
+            camera.translation *= min_y / camera.translation.y;
+        }
+    }
+}
+
+/// Largest difference between the components of two rotations, as compared
+/// by `Quat::abs_diff_eq`
+fn rotation_gap(a: Quat, b: Quat) -> f32 {
+    (Vec4::from(a) - Vec4::from(b)).abs().max_element()
+}
+
+/// Updates the slowest of `gaps` takes to close to its snap distance, when
+/// `smoothing` of each is closed every update
+fn settle_steps(gaps: &[(f32, f32)], smoothing: f32) -> f32 {
+    let per_step = (1. - smoothing.clamp(f32::EPSILON, 0.999)).ln();
+    gaps.iter()
+        .filter(|(gap, snap)| gap > snap)
+        .map(|(gap, snap)| (snap / gap).ln() / per_step)
+        .fold(0., f32::max)
+}
+
+/// Fraction of `gap` to close every update so it is within `snap` after
+/// `steps`. Aims inside `snap` so rounding can't leave it just outside
+fn settle_factor(gap: f32, snap: f32, steps: f32, smoothing: f32) -> f32 {
+    if gap <= snap {
+        smoothing
+    } else {
+        1. - (snap / 2. / gap).powf(1. / steps.max(1.))
+    }
+}
+
+/// Moves and turns the camera directly for [`CameraMode::FreeFly`]
+fn free_fly_camera(
+    camera: &mut Transform,
+    speed: f32,
+    keyboard: &KeyboardConf,
+    mouse: &MouseConf,
+    keyboard_input: &Input<KeyCode>,
+    look: Vec2,
+) {
+    let pressed = |action: ActionName, keys: &[KeyCode]| {
+        keyboard.allows(action, CameraMode::FreeFly)
+            && keys.iter().any(|key| keyboard_input.pressed(*key))
+    };
+    let mut direction = Vec3::ZERO;
+    if pressed(ActionName::Forward, &keyboard.forward) {
+        direction += camera.forward();
+    }
+    if pressed(ActionName::Backward, &keyboard.backward) {
+        direction -= camera.forward();
+    }
+    if pressed(ActionName::Right, &keyboard.right) {
+        direction += camera.right();
+    }
+    if pressed(ActionName::Left, &keyboard.left) {
+        direction -= camera.right();
+    }
+    if pressed(
+        ActionName::RotateCounterClockwise,
+        &keyboard.counter_clockwise,
+    ) {
+        direction += Vec3::Y;
+    }
+    if pressed(ActionName::RotateClockwise, &keyboard.clockwise) {
+        direction -= Vec3::Y;
+    }
+    if direction != Vec3::ZERO {
+        camera.translation += direction * speed;
+    }
+
+    if look != Vec2::ZERO {
+        camera.rotate_y(-mouse.rotate_sensitivity * look.x);
+        camera.rotate_local_x(-mouse.rotate_sensitivity * look.y);
+    }
+}
+
+/// Sent when a [`CameraRigTarget::focus_on_entity`] arrives
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FocusOnEntityCompleted(pub Entity);
+
+#[allow(clippy::type_complexity)]
+fn camera_focus(
+    time: Res<Time>,
+    mut rig_query: Query<(&CameraRig, &mut CameraRigTarget, &Transform, &Children)>,
+    camera_query: Query<&Transform, (With<Camera>, Without<CameraRig>, Without<CameraRigIgnore>)>,
+    rig_cameras: RigCameras,
+    focus_query: Query<&GlobalTransform>,
+    mut completed: EventWriter<FocusOnEntityCompleted>,
+) {
+    for (rig, mut target, rig_transform, children) in rig_query.iter_mut() {
+        let mut focus = if let Some(focus) = target.focus.take() {
+            focus
+        } else {
+            continue;
+        };
+        let camera = rig_cameras
+            .get(rig, children)
+            .and_then(|camera| camera_query.get(camera).ok())
+            .copied();
+        let (start, start_distance) = *focus.start.get_or_insert_with(|| {
+            (
+                target.rig.unwrap_or(*rig_transform).translation,
+                target
+                    .camera
+                    .or(camera)
+                    .map_or(focus.distance, |camera| camera.translation.length()),
+            )
+        });
+        if let Ok(transform) = focus_query.get(focus.entity) {
+            focus.last_position = Some(transform.translation());
+        }
+        let end = focus
+            .last_position
+            .map_or(start, |position| Vec3::new(position.x, start.y, position.z));
+
+        focus.elapsed += rig.frame_time(&time).as_secs_f32();
+        let t = if focus.duration > 0. {
+            (focus.elapsed / focus.duration).min(1.)
+        } else {
+            1.
+        };
+        let eased = t * t * (3. - 2. * t);
+
+        let mut move_to_rig = target.rig.unwrap_or(*rig_transform);
+        move_to_rig.translation = start.lerp(end, eased);
+        let move_to_camera = camera.map(|camera| {
+            let mut move_to_camera = target.camera.unwrap_or(camera);
+            move_to_camera.translation = move_to_camera.translation.normalize_or_zero()
+                * (start_distance + (focus.distance - start_distance) * eased);
+            move_to_camera
+        });
+        snap_to_animation(&mut target, move_to_rig, move_to_camera);
+
+        if t < 1. {
+            target.focus = Some(focus);
+        } else {
+            completed.send(FocusOnEntityCompleted(focus.entity));
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn camera_path_record(
+    time: Res<Time>,
+    mut rig_query: Query<(&CameraRig, &mut CameraRigTarget, &Transform, &Children)>,
+    camera_query: Query<&Transform, (With<Camera>, Without<CameraRig>, Without<CameraRigIgnore>)>,
+    rig_cameras: RigCameras,
+) {
+    for (rig, mut target, rig_transform, children) in rig_query.iter_mut() {
+        let mut recording = if let Some(recording) = target.recording.take() {
+            recording
+        } else {
+            continue;
+        };
+        let camera = rig_cameras
+            .get(rig, children)
+            .and_then(|camera| camera_query.get(camera).ok())
+            .copied()
+            .unwrap_or_default();
+        let finished = recording.elapsed >= recording.max_duration;
+        if recording.elapsed >= recording.next_sample || finished {
+            recording.path.samples.push(CameraPathSample {
+                elapsed: recording.elapsed,
+                rig: *rig_transform,
+                camera,
+            });
+            // Keep to the interval rather than drifting by part of a frame
+            // each sample, unless a long frame skipped past it
+            recording.next_sample += rig.record_interval_secs;
+            if recording.next_sample <= recording.elapsed {
+                recording.next_sample = recording.elapsed + rig.record_interval_secs;
+            }
+        }
+        recording.elapsed += rig.frame_time(&time).as_secs_f32();
+
+        if finished {
+            target.recorded_path = Some(recording.path);
+        } else {
+            target.recording = Some(recording);
+        }
+    }
+}
+
+/// Moves the rig and its camera to where an animation has them this update.
+/// Snapping every update makes them follow the animation exactly, rather than
+/// smoothing towards it and falling behind
+fn snap_to_animation(target: &mut CameraRigTarget, rig: Transform, camera: Option<Transform>) {
+    target.rig = Some(rig);
+    if camera.is_some() {
+        target.camera = camera;
+    }
+    target.snap_next_update = true;
+}
+
+fn camera_path_playback(
+    time: Res<Time>,
+    mut rig_query: Query<(&CameraRig, &mut CameraRigTarget, &Children)>,
+    rig_cameras: RigCameras,
+) {
+    for (rig, mut target, children) in rig_query.iter_mut() {
+        let mut playback = if let Some(playback) = target.path_playback.take() {
+            playback
+        } else {
+            continue;
+        };
+        if let Some((rig_pose, camera_pose)) = playback.path.sample(playback.elapsed) {
+            let camera_pose = rig_cameras.get(rig, children).and(Some(camera_pose));
+            snap_to_animation(&mut target, rig_pose, camera_pose);
+        }
+        playback.elapsed += rig.frame_time(&time).as_secs_f32();
+        if playback.elapsed <= playback.path.duration() {
+            target.path_playback = Some(playback);
+        }
+    }
+}
+
+/// Sent when a rig's camera zooms across one of its
+/// [`CameraRig::zoom_level_thresholds`]. `level` is how many thresholds the
+/// camera is further away than
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZoomLevelChanged {
+    pub rig: Entity,
+    pub level: u8,
+}
+
+fn camera_zoom_level(
+    mut rig_query: Query<(Entity, &CameraRig, &mut CameraRigTarget, &Children)>,
+    camera_query: Query<&Transform, (With<Camera>, Without<CameraRigIgnore>)>,
+    rig_cameras: RigCameras,
+    mut zoom_level_events: EventWriter<ZoomLevelChanged>,
+) {
+    for (entity, rig, mut target, children) in rig_query.iter_mut() {
+        if rig.zoom_level_thresholds.is_empty() {
+            continue;
+        }
+        let distance = if let Some(camera) = rig_cameras
+            .get(rig, children)
+            .and_then(|camera| camera_query.get(camera).ok())
+        {
+            camera.translation.length()
+        } else {
+            continue;
+        };
+        let level = rig
+            .zoom_level_thresholds
+            .iter()
+            .filter(|threshold| distance > **threshold)
+            .count() as u8;
+        if target.zoom_level != Some(level) {
+            // The first level is just recorded, it hasn't changed
+            if target.zoom_level.is_some() {
+                zoom_level_events.send(ZoomLevelChanged { rig: entity, level });
+            }
+            target.zoom_level = Some(level);
+        }
+    }
+}
+
+/// Rotates the camera about its view direction so its right is horizontal,
+/// returning whether it had any roll to remove
+fn remove_roll(camera: &mut Transform) -> bool {
+    let forward = camera.forward();
+    if camera.right().y.abs() <= 0.00001 || forward.cross(Vec3::Y).length_squared() <= f32::EPSILON
+    {
+        return false;
+    }
+    let rotation = camera.rotation;
+    camera.look_at(camera.translation + forward, Vec3::Y);
+    // Keep the same sign so it still compares and lerps against the target
+    if camera.rotation.dot(rotation) < 0. {
+        camera.rotation = -camera.rotation;
+    }
+    true
+}
+
+/// Lowest the camera can get above [`CameraRig::pan_floor`]
+const PAN_FLOOR_CLEARANCE: f32 = 0.1;
+/// Shallowest angle in radians the camera can look down at the rig with
+/// [`CameraRig::pan_floor`] set
+const PAN_FLOOR_MIN_PITCH: f32 = 0.05;
+
+/// Steepest pitch in radians [`CameraRig::min_camera_height`] tilts the
+/// camera to before backing it away instead
+const LIFT_MAX_PITCH: f32 = 1.5;
+
+/// Horizontal pan that brings `point` back to within `margin` of the edges of
+/// the camera's view, `None` when it is already there or behind the camera
+fn keep_on_screen_pan(
+    point: Vec3,
+    margin: f32,
+    camera: &GlobalTransform,
+    projection: &Projection,
+) -> Option<Vec3> {
+    let ndc = world_to_ndc(point, camera, projection)?;
+    let limit = 1. - 2. * margin.clamp(0., 0.5);
+    let clamped = ndc
+        .truncate()
+        .clamp(Vec2::splat(-limit), Vec2::splat(limit));
+    if clamped == ndc.truncate() {
+        return None;
+    }
+    // Moving the camera from where it would show the point at `clamped` to
+    // the point shows it there, keeping to the point's height so the rig
+    // only moves along the ground
+    let ray = ndc_to_world_ray(clamped, camera, projection);
+    let shown_at = ground_intersection(ray.origin, ray.direction, point.y)?;
+    Some(point - shown_at)
+}
+
+/// Rig position over the ground that keeps what the camera shows from `rig`
+/// inside `rect`, see [`BoundsMode::VisibleArea`]. With `clamp_zoom` the
+/// camera is first moved in along its offset until the view fits
+fn clamp_visible_area(
+    rect: Rect,
+    clamp_zoom: bool,
+    rig: &Transform,
+    camera: &mut Transform,
+    projection: &Projection,
+    ortho_scale_per_distance: Option<f32>,
+    ground: f32,
+) -> Vec2 {
+    let footprint = |camera: &Transform| {
+        // An orthographic camera shows less as it zooms in only once its
+        // scale follows, so work out what it will show at this distance
+        let projection = match (projection, ortho_scale_per_distance) {
+            (Projection::Orthographic(ortho), Some(scale_per_distance)) if ortho.scale > 0. => {
+                let scale = camera.translation.length() * scale_per_distance;
+                Projection::Orthographic(OrthographicProjection {
+                    scale,
+                    area: Rect::from_center_size(
+                        ortho.area.center(),
+                        ortho.area.size() * scale / ortho.scale,
+                    ),
+                    ..ortho.clone()
+                })
+            }
+            _ => projection.clone(),
+        };
+        ground_footprint(&rig.mul_transform(*camera).into(), &projection, ground)
+    };
+
+    let mut shown = footprint(camera);
+    if clamp_zoom {
+        let fit = (rect.size() / shown.size()).min_element();
+        if fit < 1. {
+            let zoomed_in = camera.with_translation(camera.translation * fit);
+            let zoomed_in_shown = footprint(&zoomed_in);
+            // Zooming doesn't change what some cameras show, such as an
+            // orthographic camera without `ortho_scale_per_distance`
+            if zoomed_in_shown.size().max_element() < shown.size().max_element() {
+                *camera = zoomed_in;
+                shown = zoomed_in_shown;
+            }
+        }
+    }
+
+    // Range of rig positions that keep each edge of the view inside, which
+    // is empty along an axis when the view is wider than the bounds
+    let position = Vec2::new(rig.translation.x, rig.translation.z);
+    let min = rect.min - (shown.min - position);
+    let max = rect.max - (shown.max - position);
+    let centered = rect.center() - (shown.center() - position);
+    let axis = |position: f32, min: f32, max: f32, centered: f32| {
+        if min <= max {
+            position.clamp(min, max)
+        } else {
+            centered
+        }
+    };
+    Vec2::new(
+        axis(position.x, min.x, max.x, centered.x),
+        axis(position.y, min.y, max.y, centered.y),
+    )
+}
+
+/// Shortens a step taken over `delta_seconds` to at most `max_speed` per
+/// second. `f32::MAX` leaves it as it is, even over an update with no time
+fn cap_speed(step: Vec3, max_speed: f32, delta_seconds: f32) -> Vec3 {
+    if max_speed < f32::MAX {
+        step.clamp_length_max(max_speed * delta_seconds)
+    } else {
+        step
+    }
+}
+
+/// Seconds it takes smoothing to close a gap at the rate it starts closing it
+const SMOOTHING_SECS: f32 = 0.1;
+
+/// Longest step in seconds used to integrate [`FollowDynamics::Spring`] and
+/// [`CameraRig::spring_return_to_bounds`]
+const SPRING_STEP: f32 = 1. / 240.;
+
+/// Marks an entity rigs can follow, and whether they are following it
+///
+/// ```
+/// # use bevy_tests::CameraRigFollow;
+/// let follow = CameraRigFollow::new(true).with_keep_on_screen_margin(0.1);
+/// ```
+#[derive(Component, Default, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "inspector",
+    derive(Reflect, InspectorOptions),
+    reflect(Component, InspectorOptions)
+)]
+pub struct CameraRigFollow {
+    /// Whether rigs are following the entity
+    pub active: bool,
+    /// Leaves the rig where it is while the entity is on screen, only panning
+    /// when it comes within this margin of an edge to keep it that far in,
+    /// with the rig's [`CameraRig::follow_dynamics`]. A fraction of the
+    /// view's width and height, from 0 at the edges to 0.5 at the center.
+    /// `None` keeps the entity centered
+    pub keep_on_screen_margin: Option<f32>,
+}
+
+impl CameraRigFollow {
+    pub fn new(active: bool) -> Self {
+        Self {
+            active,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_keep_on_screen_margin(mut self, margin: f32) -> Self {
+        self.keep_on_screen_margin = Some(margin);
+        self
+    }
+}
+
+/// Gently pans a rig so `entity` doesn't leave the view, as for a building
+/// highlighted in a tutorial, while leaving the player free to pan around
+/// it. When it is outside `margin` of the edges of the view, as a fraction
+/// of its size, `strength` of the way back is closed per second, so pushing
+/// hard enough against a low strength still pans it off screen. Removing
+/// this or despawning the entity stops it
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tests::KeepInView;
+/// fn highlight(commands: &mut Commands, rig: Entity, building: Entity) {
+///     commands.entity(rig).insert(KeepInView {
+///         entity: building,
+///         margin: 0.1,
+///         strength: 5.,
+///     });
+/// }
+/// ```
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct KeepInView {
+    pub entity: Entity,
+    pub margin: f32,
+    pub strength: f32,
+}
+
+#[allow(clippy::type_complexity)]
+fn camera_keep_in_view(
+    time: Res<Time>,
+    mut rig_query: Query<(
+        &CameraRig,
+        &KeepInView,
+        &Transform,
+        &mut CameraRigTarget,
+        &Children,
+    )>,
+    camera_query: Query<
+        (&Transform, &Projection),
+        (With<Camera>, Without<CameraRig>, Without<CameraRigIgnore>),
+    >,
+    rig_cameras: RigCameras,
+    point_query: Query<&GlobalTransform>,
+) {
+    for (rig, keep_in_view, transform, mut target, children) in rig_query.iter_mut() {
+        if rig.disable {
+            continue;
+        }
+        let point = if let Ok(point) = point_query.get(keep_in_view.entity) {
+            point.translation()
+        } else {
+            continue;
+        };
+        let camera = rig_cameras
+            .get(rig, children)
+            .and_then(|camera| camera_query.get(camera).ok());
+        // Where the rig is heading, so the nudge adds to the player's panning
+        let mut move_to_rig = target.rig.unwrap_or(*transform);
+        if let Some(pan) = camera.and_then(|(camera_transform, projection)| {
+            let camera_transform = target.camera.unwrap_or(*camera_transform);
+            let camera = GlobalTransform::from(move_to_rig.mul_transform(camera_transform));
+            keep_on_screen_pan(point, keep_in_view.margin, &camera, projection)
+        }) {
+            let pull = (keep_in_view.strength * rig.frame_time(&time).as_secs_f32()).min(1.);
+            move_to_rig.translation += pan * pull;
+            target.rig = Some(move_to_rig);
+        }
+    }
+}
+
+/// Puts rigs on the entity they are locked to, see
+/// [`CameraRig::lock_to_entity`]
+fn camera_position_lock(
+    mut rig_query: Query<(&CameraRig, &mut Transform, &mut CameraRigTarget)>,
+    entity_query: Query<&Transform, Without<CameraRig>>,
+) {
+    for (rig, mut transform, mut target) in rig_query.iter_mut() {
+        let locked_to = match rig
+            .position_lock
+            .and_then(|entity| entity_query.get(entity).ok())
+        {
+            Some(locked_to) => locked_to.translation,
+            None => continue,
+        };
+        if transform.translation != locked_to {
+            transform.translation = locked_to;
+        }
+        // Drop any pan in flight so it doesn't pull the rig off once unlocked
+        if let Some(move_to_rig) = target.rig.as_mut() {
+            move_to_rig.translation = locked_to;
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn camera_rig_follow(
+    time: Res<Time>,
+    mut rig_query: ParamSet<(
+        Query<(
+            &CameraRig,
+            &mut Transform,
+            &mut CameraRigTarget,
+            Option<&Children>,
+        )>,
+        Query<(&Transform, &CameraRigFollow)>,
+    )>,
+    camera_query: Query<
+        (&Transform, &Projection),
+        (With<Camera>, Without<CameraRig>, Without<CameraRigIgnore>),
+    >,
+    rig_cameras: RigCameras,
+) {
+    // Keep going while the entity is still, so the rig can catch up with it
+    let follow = rig_query
+        .p1()
+        .iter()
+        .filter(|(_, follow)| follow.active)
+        .last()
+        .map(|(follow_transform, follow)| (*follow_transform, follow.keep_on_screen_margin));
+    for (rig, mut transform, mut target, children) in rig_query.p0().iter_mut() {
+        let delta = rig.frame_time(&time);
+        let paused = target.follow_unlocked
+            || target
+                .follow_reactivation_timer
+                .as_ref()
+                .is_some_and(|timer| !timer.finished());
+        let (follow_transform, margin) = match follow {
+            Some(follow) if !paused => follow,
+            _ => {
+                // Ease in again when following resumes
+                if target.follow_blend != 0.
+                    || target.follow_velocity != Vec3::ZERO
+                    || target.follow_glide.is_some()
+                {
+                    target.follow_blend = 0.;
+                    target.follow_velocity = Vec3::ZERO;
+                    target.follow_glide = None;
+                }
+                continue;
+            }
+        };
+
+        if let FollowDynamics::Locked { offset } = rig.follow_dynamics {
+            let locked = follow_transform.translation + transform.rotation * offset;
+            if target.follow_blend < 1. {
+                // Glide back after being unlocked rather than jumping, carried
+                // along with the entity so it catches up while it moves
+                let glide = target
+                    .follow_glide
+                    .get_or_insert(transform.translation - locked);
+                *glide = smooth_toward(*glide, Vec3::ZERO, delta.as_secs_f32(), SMOOTHING_SECS);
+                if glide.length() <= rig.follow_snap_epsilon {
+                    target.follow_blend = 1.;
+                    target.follow_glide = None;
+                }
+            }
+            let translation = locked + target.follow_glide.unwrap_or(Vec3::ZERO);
+            if transform.translation != translation {
+                transform.translation = translation;
+            }
+            if let Some(rig_transform) = target.rig.as_mut() {
+                rig_transform.translation = transform.translation;
+            }
+            continue;
+        }
+
+        // With a margin, only head far enough to keep the entity on screen
+        let goal = match margin {
+            Some(margin) => children
+                .and_then(|children| rig_cameras.get(rig, children))
+                .and_then(|camera| camera_query.get(camera).ok())
+                .and_then(|(camera_transform, projection)| {
+                    // The GlobalTransforms aren't propagated yet, so work from
+                    // the local ones
+                    let camera = GlobalTransform::from(transform.mul_transform(*camera_transform));
+                    keep_on_screen_pan(follow_transform.translation, margin, &camera, projection)
+                })
+                .map_or(transform.translation, |pan| transform.translation + pan),
+            None => follow_transform.translation,
+        };
+
+        if let FollowDynamics::Spring { stiffness, damping } = rig.follow_dynamics {
+            let mut translation = transform.translation;
+            let mut velocity = target.follow_velocity;
+            // Small fixed steps keep stiff springs stable at low frame rates
+            let steps = (delta.as_secs_f32() / SPRING_STEP).ceil().max(1.);
+            let dt = delta.as_secs_f32() / steps;
+            for _ in 0..steps as u32 {
+                let acceleration = (goal - translation) * stiffness - velocity * damping;
+                velocity += acceleration * dt;
+                translation += velocity * dt;
+            }
+            if translation.distance(goal) <= rig.follow_snap_epsilon
+                && velocity.length() <= rig.follow_snap_epsilon
+            {
+                translation = goal;
+                velocity = Vec3::ZERO;
+            }
+            target.follow_velocity = velocity;
+            if transform.translation != translation {
+                transform.translation = translation;
+            }
+            if let Some(rig_transform) = target.rig.as_mut() {
+                rig_transform.translation = transform.translation;
+            }
+            continue;
+        }
+
+        target.follow_blend = if rig.follow_ease_in_secs > 0. {
+            (target.follow_blend + delta.as_secs_f32() / rig.follow_ease_in_secs).min(1.)
+        } else {
+            1.
+        };
+
+        if goal != transform.translation {
+            if goal.distance(transform.translation).abs() > rig.follow_snap_epsilon {
+                transform.translation = smooth_toward(
+                    transform.translation,
+                    goal,
+                    delta.as_secs_f32() * target.follow_blend,
+                    SMOOTHING_SECS,
+                );
+            } else {
+                transform.translation = goal;
+            }
+        }
+
+        // Also update the rig translation, so when following stops manual
+        // movement carries on from where the rig actually is
+        if let Some(rig_transform) = target.rig.as_mut() {
+            rig_transform.translation = transform.translation;
+        }
+    }
+}
+
+/// Height of the horizontal plane that screen positions are projected onto,
+/// 0 by default. Everything that finds the ground under the cursor or the
+/// view uses it: [`CursorGroundPosition`], [`CameraGroundFocus`],
+/// [`CameraRigCursor`], [`ScreenToWorld`], zoom anchors, orbit pivots and the
+/// return from free fly. Insert it with the playable plane's height for maps
+/// that aren't at the origin
+#[derive(Resource, Default)]
+#[doc(alias = "ground_height")]
+pub struct GroundPlane(pub f32);
+
+/// Point on the [`GroundPlane`] under the cursor, `None` when the cursor is
+/// outside the window or pointing at the sky above the horizon. Updated in
+/// [`CameraRaycastSet`] from the first active rig camera, so it is current for
+/// this frame there and after, and anything written to it is overwritten
+#[derive(Resource, Default)]
+pub struct CursorGroundPosition(pub Option<Vec3>);
+
+/// [`CursorGroundPosition`] by the name it is also known as
+pub type CursorGroundHit = CursorGroundPosition;
+
+impl CursorGroundPosition {
+    /// Whether the cursor is over the ground, for picking a cursor or
+    /// checking a command can target it
+    pub fn is_over_ground(&self) -> bool {
+        self.0.is_some()
+    }
+}
+
+/// Converts between screen and world positions using the rig's camera. This
+/// reads the camera's `GlobalTransform`, so use it in or after
+/// [`CameraRaycastSet`] to avoid being a frame behind
+#[derive(SystemParam)]
+pub struct ScreenToWorld<'w, 's> {
+    rigs: Query<'w, 's, (&'static CameraRig, &'static Children)>,
+    rig_cameras: RigCameras<'w, 's>,
+    cameras: Query<'w, 's, (&'static Camera, &'static GlobalTransform)>,
+    ground: Res<'w, GroundPlane>,
+}
+
+impl<'w, 's> ScreenToWorld<'w, 's> {
+    /// The first active camera found under a rig
+    pub fn camera(&self) -> Option<(&Camera, &GlobalTransform)> {
+        self.rigs
+            .iter()
+            .filter_map(|(rig, children)| self.rig_cameras.get(rig, children))
+            .filter_map(|camera| self.cameras.get(camera).ok())
+            .find(|(camera, _)| camera.is_active)
+    }
+
+    pub fn world_to_viewport(&self, world_position: Vec3) -> Option<Vec2> {
+        let (camera, camera_transform) = self.camera()?;
+        camera.world_to_viewport(camera_transform, world_position)
+    }
+
+    /// Projects a viewport position onto the [`GroundPlane`]
+    pub fn viewport_to_world_plane(&self, viewport_position: Vec2) -> Option<Vec3> {
+        let (camera, camera_transform) = self.camera()?;
+        let ray = camera.viewport_to_world(camera_transform, viewport_position)?;
+        ground_intersection(ray.origin, ray.direction, self.ground.0)
+    }
+}
+
+fn cursor_ground_position(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    screen_to_world: ScreenToWorld,
+    mut cursor_ground: ResMut<CursorGroundPosition>,
+) {
+    let position = windows
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .and_then(|cursor| screen_to_world.viewport_to_world_plane(cursor));
+    if cursor_ground.0 != position {
+        cursor_ground.0 = position;
+    }
+}
+
+/// Point on the [`GroundPlane`] at the center of the rig camera's view,
+/// `None` when the camera is looking above the horizon. Updated by the plugin
+#[derive(Component, Default, Clone, Copy, Debug)]
+pub struct CameraGroundFocus(pub Option<Vec3>);
+
+fn camera_ground_focus(
+    mut rig_query: Query<(&CameraRig, &mut CameraGroundFocus, &Children)>,
+    cameras: Query<(&Camera, &GlobalTransform), Without<CameraRigIgnore>>,
+    rig_cameras: RigCameras,
+    ground: Res<GroundPlane>,
+) {
+    for (rig, mut focus, children) in rig_query.iter_mut() {
+        let position = rig_cameras
+            .get(rig, children)
+            .and_then(|camera| cameras.get(camera).ok())
+            .and_then(|(camera, camera_transform)| {
+                let center = camera.logical_viewport_size()? / 2.;
+                let ray = camera.viewport_to_world(camera_transform, center)?;
+                ground_intersection(ray.origin, ray.direction, ground.0)
+            });
+        if focus.0 != position {
+            focus.0 = position;
+        }
+    }
+}
+
+/// Where the cursor is on screen and on the [`GroundPlane`], as seen by the
+/// rig's camera. Updated by the plugin
+#[derive(Component, Default, Clone, Copy, Debug)]
+pub struct CameraRigCursor {
+    pub world_position: Vec3,
+    pub screen_position: Vec2,
+    /// False when the cursor is outside the window or doesn't hit the ground
+    pub valid: bool,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn camera_cursor_system(
+    mut cursor_moved_events: EventReader<CursorMoved>,
+    mut cursor_left_events: EventReader<CursorLeft>,
+    mut screen_position: Local<Option<Vec2>>,
+    mut rig_query: Query<(&CameraRig, &mut CameraRigCursor, &Children)>,
+    cameras: Query<(&Camera, &GlobalTransform), Without<CameraRigIgnore>>,
+    windows: Query<&Window>,
+    rig_cameras: RigCameras,
+    ground: Res<GroundPlane>,
+) {
+    if let Some(event) = cursor_moved_events.iter().last() {
+        *screen_position = Some(event.position);
+    }
+    // The two kinds of event can't be put in order, so after a `CursorLeft`
+    // ask the window whether the cursor has come back since
+    if let Some(event) = cursor_left_events.iter().last() {
+        if windows
+            .get(event.window)
+            .map_or(true, |window| window.cursor_position().is_none())
+        {
+            *screen_position = None;
+        }
+    }
+
+    for (rig, mut cursor, children) in rig_query.iter_mut() {
+        let world_position = screen_position.and_then(|position| {
+            let (camera, camera_transform) = rig_cameras
+                .get(rig, children)
+                .and_then(|camera| cameras.get(camera).ok())?;
+            let ray = camera.viewport_to_world(camera_transform, position)?;
+            ground_intersection(ray.origin, ray.direction, ground.0)
+        });
+
+        let updated = CameraRigCursor {
+            world_position: world_position.unwrap_or(cursor.world_position),
+            screen_position: screen_position.unwrap_or(cursor.screen_position),
+            valid: world_position.is_some(),
+        };
+        if updated.world_position != cursor.world_position
+            || updated.screen_position != cursor.screen_position
+            || updated.valid != cursor.valid
+        {
+            *cursor = updated;
+        }
+    }
+}
+
+/// The rig that global camera effects apply to, when `None` the first rig
+/// found is used
+#[derive(Resource, Default)]
+pub struct ActiveCameraRig(pub Option<Entity>);
+
+/// The game's selected unit or city, kept up to date by the game for
+/// [`CameraAction::ToggleFollowSelected`], which does nothing when `None`
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tests::SelectedEntity;
+/// # #[derive(Component)]
+/// # struct Selected;
+/// fn track_selection(
+///     selected: Query<Entity, Added<Selected>>,
+///     mut selected_entity: ResMut<SelectedEntity>,
+/// ) {
+///     if let Some(entity) = selected.iter().next() {
+///         selected_entity.0 = Some(entity);
+///     }
+/// }
+/// ```
+#[derive(Resource, Default)]
+pub struct SelectedEntity(pub Option<Entity>);
+
+impl ActiveCameraRig {
+    /// The active rig and its children
+    pub fn get<'a>(
+        &self,
+        rigs: &'a Query<(Entity, &Children), With<CameraRig>>,
+    ) -> Option<(Entity, &'a Children)> {
+        if let Some(entity) = self.0 {
+            rigs.get(entity).ok()
+        } else {
+            rigs.iter().next()
+        }
+    }
+}
+
+/// Blends what is rendered from one rig's camera to another's, rather than
+/// cutting between them
+#[derive(Resource, Default)]
+pub struct CameraBlend {
+    blend: Option<BlendState>,
+    // Camera transform and fov from before the blend was applied this frame
+    applied: Option<(Entity, Transform, Option<f32>)>,
+}
+
+struct BlendState {
+    from: Entity,
+    to: Entity,
+    duration: Duration,
+    easing: SmootherKind,
+    elapsed: Duration,
+    /// Pose to start from instead of the `from` camera, when retargeted
+    /// mid-blend
+    start: Option<(Transform, Option<f32>)>,
+    /// Last blended pose
+    current: Option<(Transform, Option<f32>)>,
+    started: bool,
+}
+
+impl CameraBlend {
+    /// Moves the view from `from_rig`'s camera to `to_rig`'s over `duration`,
+    /// blending their positions, rotations and perspective fovs along
+    /// `easing`, then sends a [`CameraBlendFinished`]. `to_rig` becomes the
+    /// [`ActiveCameraRig`] with its camera active, and takes input straight
+    /// away while `from_rig` is disabled. Blending again before this finishes
+    /// starts from wherever the view is. Rigs are expected to be top level
+    /// entities
+    ///
+    /// ```
+    /// # use bevy::{prelude::*, utils::Duration};
+    /// # use bevy_tests::{CameraBlend, SmootherKind};
+    /// // Zoom from the strategic map into a battle
+    /// fn enter_battle(mut blend: ResMut<CameraBlend>, strategic: Entity, tactical: Entity) {
+    ///     blend.blend(
+    ///         strategic,
+    ///         tactical,
+    ///         Duration::from_millis(500),
+    ///         SmootherKind::SmoothStep,
+    ///     );
+    /// }
+    /// ```
+    pub fn blend(
+        &mut self,
+        from_rig: Entity,
+        to_rig: Entity,
+        duration: Duration,
+        easing: SmootherKind,
+    ) {
+        let start = self.blend.as_ref().and_then(|blend| blend.current);
+        self.blend = Some(BlendState {
+            from: from_rig,
+            to: to_rig,
+            duration,
+            easing,
+            elapsed: Duration::ZERO,
+            start,
+            current: None,
+            started: false,
+        });
+    }
+
+    pub fn is_blending(&self) -> bool {
+        self.blend.is_some()
+    }
+}
+
+/// Sent when a [`CameraBlend`] reaches its destination rig
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CameraBlendFinished {
+    pub from: Entity,
+    pub to: Entity,
+}
+
+fn camera_blend_reset(
+    mut blend: ResMut<CameraBlend>,
+    mut camera_query: Query<(&mut Transform, Option<&mut Projection>)>,
+) {
+    if let Some((camera, transform, fov)) = blend.applied.take() {
+        if let Ok((mut camera_transform, projection)) = camera_query.get_mut(camera) {
+            *camera_transform = transform;
+            if let (Some(fov), Some(mut projection)) = (fov, projection) {
+                if let Projection::Perspective(perspective) = projection.as_mut() {
+                    perspective.fov = fov;
+                }
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn camera_blend(
+    time: Res<Time>,
+    mut blend: ResMut<CameraBlend>,
+    mut active_rig: ResMut<ActiveCameraRig>,
+    mut rig_query: Query<(&mut CameraRig, &Transform, &Children)>,
+    mut camera_query: Query<
+        (&mut Camera, &mut Transform, Option<&mut Projection>),
+        (Without<CameraRig>, Without<CameraRigIgnore>),
+    >,
+    rig_cameras: RigCameras,
+    mut finished: EventWriter<CameraBlendFinished>,
+) {
+    let blend = &mut *blend;
+    let state = if let Some(state) = blend.blend.as_mut() {
+        state
+    } else {
+        return;
+    };
+    let rig_camera = |rig: Entity| {
+        let (camera_rig, rig_transform, children) = rig_query.get(rig).ok()?;
+        let camera = rig_cameras.get(camera_rig, children)?;
+        Some((*rig_transform, camera))
+    };
+    let (to_rig, to_camera) = match rig_camera(state.to) {
+        Some(to) => to,
+        None => {
+            warn!("Blending to a camera rig without a camera");
+            blend.blend = None;
+            return;
+        }
+    };
+    let from = rig_camera(state.from);
+
+    if !state.started {
+        state.started = true;
+        if let Ok((mut rig, ..)) = rig_query.get_mut(state.from) {
+            rig.disable = true;
+        }
+        if let Ok((mut rig, ..)) = rig_query.get_mut(state.to) {
+            rig.disable = false;
+        }
+        if let Some((_, from_camera)) = from {
+            if let Ok((mut camera, ..)) = camera_query.get_mut(from_camera) {
+                camera.is_active = false;
+            }
+        }
+        if let Ok((mut camera, ..)) = camera_query.get_mut(to_camera) {
+            camera.is_active = true;
+        }
+        active_rig.0 = Some(state.to);
+    }
+
+    let pose = |rig: Transform, camera: Entity| {
+        let (_, transform, projection) = camera_query.get(camera).ok()?;
+        let fov = match projection {
+            Some(Projection::Perspective(perspective)) => Some(perspective.fov),
+            _ => None,
+        };
+        Some((rig.mul_transform(*transform), fov))
+    };
+    let start = state
+        .start
+        .or_else(|| from.and_then(|(rig, camera)| pose(rig, camera)));
+    let (end, end_fov) = pose(to_rig, to_camera).unwrap_or_default();
+    let (start, start_fov) = start.unwrap_or((end, end_fov));
+
+    let delta = rig_query
+        .get(state.to)
+        .map_or(time.delta(), |(rig, ..)| rig.frame_time(&time));
+    state.elapsed += delta;
+    let t = if state.duration.is_zero() {
+        1.
+    } else {
+        (state.elapsed.as_secs_f32() / state.duration.as_secs_f32()).min(1.)
+    };
+    if t >= 1. {
+        finished.send(CameraBlendFinished {
+            from: state.from,
+            to: state.to,
+        });
+        blend.blend = None;
+        return;
+    }
+
+    let eased = apply_smoothstep(&state.easing, t);
+    let view = blend_transforms(&start, &end, eased);
+    let fov = start_fov
+        .zip(end_fov)
+        .map(|(start, end)| start + (end - start) * eased);
+    state.current = Some((view, fov));
+
+    if let Ok((_, mut transform, projection)) = camera_query.get_mut(to_camera) {
+        let mut original_fov = None;
+        if let (Some(fov), Some(mut projection)) = (fov, projection) {
+            if let Projection::Perspective(perspective) = projection.as_mut() {
+                original_fov = Some(perspective.fov);
+                perspective.fov = fov;
+            }
+        }
+        blend.applied = Some((to_camera, *transform, original_fov));
+        *transform =
+            Transform::from_matrix(to_rig.compute_matrix().inverse() * view.compute_matrix());
+    }
+}
+
+/// Shows rigs with [`CameraRig::pixel_snap`] on the pixel grid, remembering
+/// the offset so the next update carries on from where they really are
+#[allow(clippy::type_complexity)]
+fn camera_pixel_snap(
+    mut rig_query: Query<(
+        &CameraRig,
+        &mut Transform,
+        &mut CameraRigTarget,
+        Option<&Children>,
+    )>,
+    camera_query: Query<&Transform, (With<Camera>, Without<CameraRig>, Without<CameraRigIgnore>)>,
+    rig_cameras: RigCameras,
+) {
+    for (rig, mut transform, mut target, children) in rig_query.iter_mut() {
+        let unit = if let Some(unit) = rig.pixel_snap.filter(|unit| *unit > 0.) {
+            unit
+        } else {
+            continue;
+        };
+        let rotation = if rig.pixel_snap_rotation == PixelSnapRotation::QuarterTurns {
+            let (yaw, _, _) = transform.rotation.to_euler(EulerRot::YXZ);
+            let quarter = std::f32::consts::FRAC_PI_2;
+            Quat::from_rotation_y((yaw / quarter).round() * quarter) * transform.rotation.inverse()
+        } else {
+            Quat::IDENTITY
+        };
+        let snap = |value: f32| (value / unit).round() * unit - value;
+        let camera = children
+            .and_then(|children| rig_cameras.get(rig, children))
+            .and_then(|camera| camera_query.get(camera).ok());
+        let translation = if let Some(camera) = camera {
+            // The pixel grid lies along the camera's right and up, so snap
+            // the rig's position on screen. Moving along the view doesn't show,
+            // so use that to keep the rig at its height
+            let view = rotation * transform.rotation * camera.rotation;
+            let (right, up, forward) = (view * Vec3::X, view * Vec3::Y, view * Vec3::NEG_Z);
+            let on_screen = right * snap(transform.translation.dot(right))
+                + up * snap(transform.translation.dot(up));
+            if forward.y.abs() > f32::EPSILON {
+                on_screen - forward * (on_screen.y / forward.y)
+            } else {
+                on_screen
+            }
+        } else {
+            (transform.translation / unit).round() * unit - transform.translation
+        };
+        if translation != Vec3::ZERO || rotation != Quat::IDENTITY {
+            transform.translation += translation;
+            transform.rotation = rotation * transform.rotation;
+            target.pixel_snap_offset = (translation, rotation);
+        }
+    }
+}
+
+fn camera_pixel_snap_reset(mut rig_query: Query<(&mut Transform, &mut CameraRigTarget)>) {
+    for (mut transform, mut target) in rig_query.iter_mut() {
+        let (translation, rotation) = target.pixel_snap_offset;
+        if translation != Vec3::ZERO || rotation != Quat::IDENTITY {
+            transform.translation -= translation;
+            transform.rotation = rotation.inverse() * transform.rotation;
+            target.pixel_snap_offset = (Vec3::ZERO, Quat::IDENTITY);
+        }
+    }
+}
+
+/// Trauma based shake for a rig's camera, added to the camera entity
+#[derive(Component, Clone, Debug)]
+pub struct CameraShake {
+    /// Between 0 and 1, the shake strength is the square of this
+    pub trauma: f32,
+    /// Trauma lost per second
+    pub decay: f32,
+    /// Offset in the camera's local space at full trauma
+    pub max_offset: Vec3,
+    /// Roll in radians at full trauma
+    pub max_roll: f32,
+    /// How many times a second the shake changes direction
+    pub frequency: f32,
+    /// Picks the shake pattern. The pattern runs from when the camera starts
+    /// shaking, so the same seed and trauma shake the same way every time,
+    /// for replays and tests. [`CameraRig::set_shake_seed`] sets it from the
+    /// rig
+    ///
+    /// ```
+    /// # use bevy_tests::CameraShake;
+    /// // The same shake every time the boss lands
+    /// let mut shake = CameraShake::default();
+    /// shake.seed = 7;
+    /// shake.add_trauma(1.);
+    /// ```
+    pub seed: u64,
+    // Seconds the camera has been shaking for
+    elapsed: f32,
+    // Translation and rotation applied this frame, removed before the next
+    applied: (Vec3, Quat),
+}
+
+impl Default for CameraShake {
+    fn default() -> Self {
+        CameraShake {
+            trauma: 0.,
+            decay: 1.,
+            max_offset: Vec3::new(0.5, 0.5, 0.),
+            max_roll: 0.05,
+            frequency: 15.,
+            seed: 0,
+            elapsed: 0.,
+            applied: (Vec3::ZERO, Quat::IDENTITY),
+        }
+    }
+}
+
+impl CameraShake {
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).clamp(0., 1.);
+    }
+}
+
+/// Sets the [`CameraShake::seed`] of a rig's cameras, see
+/// [`CameraRig::set_shake_seed`]
+struct SetShakeSeed {
+    rig: Entity,
+    seed: u64,
+}
+
+impl Command for SetShakeSeed {
+    fn write(self, world: &mut World) {
+        let children = if let Some(children) = world.get::<Children>(self.rig) {
+            children.to_vec()
+        } else {
+            return;
+        };
+        for child in children {
+            if let Some(mut shake) = world.get_mut::<CameraShake>(child) {
+                shake.seed = self.seed;
+            }
+        }
+    }
+}
+
+/// Stops a rig and its camera's shake, see [`CameraRig::stop`]
+struct StopRig {
+    rig: Entity,
+}
+
+impl Command for StopRig {
+    fn write(self, world: &mut World) {
+        if let Some(mut target) = world.get_mut::<CameraRigTarget>(self.rig) {
+            target.stop();
+        }
+        let children = if let Some(children) = world.get::<Children>(self.rig) {
+            children.to_vec()
+        } else {
+            return;
+        };
+        for child in children {
+            if let Some(mut shake) = world.get_mut::<CameraShake>(child) {
+                shake.trauma = 0.;
+            }
+        }
+    }
+}
+
+fn camera_shake_reset(mut shake_query: Query<(&mut Transform, &mut CameraShake)>) {
+    for (mut transform, mut shake) in shake_query.iter_mut() {
+        let (translation, rotation) = shake.applied;
+        if translation != Vec3::ZERO || rotation != Quat::IDENTITY {
+            transform.translation -= translation;
+            transform.rotation *= rotation.inverse();
+            shake.applied = (Vec3::ZERO, Quat::IDENTITY);
+        }
+    }
+}
+
+fn camera_shake(
+    time: Res<Time>,
+    mut shake_query: Query<(&mut Transform, &mut CameraShake, Option<&Parent>)>,
+    rig_query: Query<&CameraRig>,
+) {
+    for (mut transform, mut shake, parent) in shake_query.iter_mut() {
+        if shake.trauma <= 0. {
+            if shake.elapsed != 0. {
+                shake.elapsed = 0.;
+            }
+            continue;
+        }
+        let strength = shake.trauma * shake.trauma;
+        let t = shake.elapsed * shake.frequency;
+        let seed = shake.seed;
+        let noise = |channel: u32| shake_noise(seed, channel, t);
+        let roll = Quat::from_rotation_z(shake.max_roll * strength * noise(3));
+        transform.rotation *= roll;
+        let translation = transform.rotation
+            * (shake.max_offset * strength * Vec3::new(noise(0), noise(1), noise(2)));
+        transform.translation += translation;
+        shake.applied = (translation, roll);
+        // Keep to the rig's clock, so a paused game doesn't freeze the shake
+        // of a rig that ignores the pause
+        let delta = parent
+            .and_then(|parent| rig_query.get(parent.get()).ok())
+            .map_or(time.delta_seconds(), |rig| {
+                rig.frame_time(&time).as_secs_f32()
+            });
+        shake.elapsed += delta;
+        shake.trauma = (shake.trauma - shake.decay * delta).max(0.);
+    }
+}
+
+/// Smooth value noise between -1 and 1, with a different pattern per seed and
+/// channel
+fn shake_noise(seed: u64, channel: u32, t: f32) -> f32 {
+    // SplitMix64's finalizer, so every bit of the seed changes the pattern
+    fn mix(mut x: u64) -> u64 {
+        x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        x ^ (x >> 31)
+    }
+    let seed = mix(seed);
+    let hash = |i: i32| {
+        let x = mix(seed
+            ^ u64::from(i as u32).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ u64::from(channel).wrapping_mul(0xC2B2_AE3D_27D4_EB4F));
+        (x >> 32) as f32 / u32::MAX as f32 * 2. - 1.
+    };
+    let i = t.floor();
+    let f = t - i;
+    let f = f * f * (3. - 2. * f);
+    hash(i as i32) * (1. - f) + hash(i as i32 + 1) * f
+}
+
+pub trait CameraShakeAppExt {
+    /// Adds `trauma` to the [`CameraShake`] of the [`ActiveCameraRig`]'s
+    /// camera every time an `E` event is sent
+    fn add_camera_shake_trigger<E: Event>(&mut self, trauma: f32) -> &mut Self;
+}
+
+impl CameraShakeAppExt for App {
+    fn add_camera_shake_trigger<E: Event>(&mut self, trauma: f32) -> &mut Self {
+        self.insert_resource(CameraShakeTrigger::<E> {
+            trauma,
+            event: PhantomData,
+        })
+        .add_system(camera_shake_trigger::<E>)
+    }
+}
+
+#[derive(Resource)]
+struct CameraShakeTrigger<E: Event> {
+    trauma: f32,
+    event: PhantomData<fn() -> E>,
+}
+
+fn camera_shake_trigger<E: Event>(
+    trigger: Res<CameraShakeTrigger<E>>,
+    mut events: EventReader<E>,
+    active_rig: Res<ActiveCameraRig>,
+    rigs: Query<(Entity, &Children), With<CameraRig>>,
+    mut shake_query: Query<&mut CameraShake>,
+) {
+    let count = events.iter().count();
+    if count == 0 {
+        return;
+    }
+    if let Some((_, children)) = active_rig.get(&rigs) {
+        for child in children.iter() {
+            if let Ok(mut shake) = shake_query.get_mut(*child) {
+                shake.add_trauma(trigger.trauma * count as f32);
+            }
+        }
+    }
+}
@@ -0,0 +1,2055 @@
+use bevy::{
+    asset::AssetPlugin,
+    ecs::system::CommandQueue,
+    input::{
+        gamepad::{GamepadButtonChangedEvent, GamepadEvent},
+        mouse::{MouseButtonInput, MouseScrollUnit},
+        ButtonState, InputPlugin,
+    },
+    render::camera::CameraProjectionPlugin,
+    window::{WindowPlugin, WindowResolution},
+};
+use std::f32::consts::PI;
+
+use super::*;
+
+/// An app with the plugin and everything it needs, but no window
+fn test_app() -> App {
+    test_app_with(FourXCameraPlugin::default())
+}
+
+fn test_app_with(plugin: FourXCameraPlugin) -> App {
+    let mut app = App::new();
+    app.add_plugin(InputPlugin)
+        .add_plugin(TransformPlugin)
+        .add_plugin(WindowPlugin {
+            primary_window: None,
+            ..Default::default()
+        })
+        .add_plugin(plugin)
+        .init_resource::<Time>();
+    app
+}
+
+/// Runs one update 16ms after the last
+fn step(app: &mut App) {
+    step_by(app, Duration::from_millis(16));
+}
+
+fn step_by(app: &mut App, delta: Duration) {
+    let mut time = app.world.resource_mut::<Time>();
+    let last = time.last_update().unwrap_or_else(|| time.startup());
+    time.update_with_instant(last + delta);
+    app.update();
+}
+
+/// A rig with a camera `offset` from it, looking at it
+fn spawn_rig(app: &mut App, camera_rig: CameraRig, offset: Vec3) -> (Entity, Entity) {
+    let mut camera = Entity::PLACEHOLDER;
+    let rig = app
+        .world
+        .spawn(CameraRigBundle {
+            camera_rig,
+            ..Default::default()
+        })
+        .with_children(|rig| {
+            camera = rig
+                .spawn(Camera3dBundle {
+                    transform: Transform::from_translation(offset).looking_at(Vec3::ZERO, Vec3::Y),
+                    ..Default::default()
+                })
+                .id();
+        })
+        .id();
+    (rig, camera)
+}
+
+fn translation(app: &App, entity: Entity) -> Vec3 {
+    app.world.get::<Transform>(entity).unwrap().translation
+}
+
+fn press_gamepad_button(app: &mut App, button: GamepadButtonType, value: f32) {
+    app.world
+        .send_event(GamepadEvent::Button(GamepadButtonChangedEvent::new(
+            Gamepad::new(0),
+            button,
+            value,
+        )));
+}
+
+#[test]
+fn auto_attach_camera_adds_rig_camera() {
+    let mut app = test_app_with(FourXCameraPlugin {
+        auto_attach_camera: true,
+    });
+    let rig = app.world.spawn(CameraRigBundle::default()).id();
+    app.update();
+
+    let children = app.world.get::<Children>(rig).unwrap();
+    assert_eq!(children.len(), 1);
+    assert!(app.world.get::<Camera>(children[0]).is_some());
+    assert!(app.world.get::<RigCamera>(children[0]).is_some());
+}
+
+#[test]
+fn gamepad_button_binding_triggers_action() {
+    let mut app = test_app();
+    let mut gamepad = GamepadConf::default();
+    gamepad
+        .button_bindings
+        .insert(CameraAction::ResetView, vec![GamepadButtonType::South]);
+    let (rig, _) = spawn_rig(
+        &mut app,
+        CameraRig {
+            gamepad,
+            ..Default::default()
+        },
+        Vec3::new(-75., 75., 0.),
+    );
+    app.update();
+
+    app.world.get_mut::<Transform>(rig).unwrap().translation = Vec3::new(100., 0., 0.);
+    press_gamepad_button(&mut app, GamepadButtonType::South, 1.);
+    for _ in 0..10 {
+        step(&mut app);
+    }
+    assert!(translation(&app, rig).x < 100.);
+}
+
+#[test]
+fn zoom_invert_gamepad_swaps_zoom_buttons() {
+    let zoomed = |gamepad: GamepadConf| {
+        let mut app = test_app();
+        let (_, camera) = spawn_rig(
+            &mut app,
+            CameraRig {
+                gamepad,
+                ..Default::default()
+            },
+            Vec3::new(-75., 75., 0.),
+        );
+        app.update();
+        press_gamepad_button(&mut app, GamepadButtonType::DPadUp, 1.);
+        for _ in 0..60 {
+            step(&mut app);
+        }
+        translation(&app, camera).length()
+    };
+    let distance = Vec3::new(-75., 75., 0.).length();
+    assert!(zoomed(GamepadConf::standard()) < distance - 1.);
+    assert!(zoomed(GamepadConf::standard().with_zoom_invert_gamepad(true)) > distance + 1.);
+}
+
+#[test]
+fn render_layers_picks_camera() {
+    let mut app = test_app();
+    let start = Transform::from_xyz(-75., 75., 0.).looking_at(Vec3::ZERO, Vec3::Y);
+    let (mut world_camera, mut ui_camera) = (Entity::PLACEHOLDER, Entity::PLACEHOLDER);
+    app.world
+        .spawn(CameraRigBundle {
+            camera_rig: CameraRig {
+                render_layers: Some(RenderLayers::layer(1)),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with_children(|rig| {
+            ui_camera = rig
+                .spawn(Camera3dBundle {
+                    transform: start,
+                    ..Default::default()
+                })
+                .id();
+            world_camera = rig
+                .spawn((
+                    Camera3dBundle {
+                        transform: start,
+                        ..Default::default()
+                    },
+                    RenderLayers::layer(1),
+                ))
+                .id();
+        });
+    app.world.send_event(CameraCommand::ZoomTo {
+        distance: 50.,
+        snap: true,
+    });
+    app.update();
+
+    assert!((translation(&app, world_camera).length() - 50.).abs() < 0.001);
+    assert_eq!(*app.world.get::<Transform>(ui_camera).unwrap(), start);
+}
+
+#[test]
+fn disable_input_during_animation() {
+    fn focus_pressing_w(disable_input_during_animation: bool) -> (Vec3, bool) {
+        let mut app = test_app();
+        let (rig, _) = spawn_rig(
+            &mut app,
+            CameraRig {
+                disable_input_during_animation,
+                ..Default::default()
+            },
+            Vec3::new(-10., 10., 0.),
+        );
+        let city = app
+            .world
+            .spawn(TransformBundle::from_transform(Transform::from_xyz(
+                50., 0., 50.,
+            )))
+            .id();
+        app.world
+            .get_mut::<CameraRigTarget>(rig)
+            .unwrap()
+            .focus_on_entity(city, 10., 0.5);
+        app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::W);
+
+        for _ in 0..10 {
+            step(&mut app);
+        }
+        (
+            translation(&app, rig),
+            app.world
+                .get::<CameraRigTarget>(rig)
+                .unwrap()
+                .focus
+                .is_some(),
+        )
+    }
+
+    // Still flying over the city
+    let (position, focusing) = focus_pressing_w(true);
+    assert!(focusing);
+    assert!((position.x - position.z).abs() < 0.0001);
+
+    // Taken over by the forward key
+    let (position, focusing) = focus_pressing_w(false);
+    assert!(!focusing);
+    assert!((position.x - position.z).abs() > 0.1);
+
+    // Nor does the wheel change the fov
+    let mut app = test_app();
+    let (rig, camera) = spawn_rig(
+        &mut app,
+        CameraRig {
+            disable_input_during_animation: true,
+            mouse: MouseConf {
+                fov_zoom: Some(FovZoomConf::default()),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Vec3::new(-10., 10., 0.),
+    );
+    // Within the fov zoom's limits
+    app.world
+        .entity_mut(camera)
+        .insert(Projection::Perspective(PerspectiveProjection {
+            fov: 0.4,
+            ..Default::default()
+        }));
+    let fov = |app: &App| match app.world.get::<Projection>(camera).unwrap() {
+        Projection::Perspective(perspective) => perspective.fov,
+        _ => unreachable!(),
+    };
+    let city = app
+        .world
+        .spawn(TransformBundle::from_transform(Transform::from_xyz(
+            50., 0., 50.,
+        )))
+        .id();
+    app.world
+        .get_mut::<CameraRigTarget>(rig)
+        .unwrap()
+        .focus_on_entity(city, 10., 0.5);
+    let before = fov(&app);
+    app.world.send_event(MouseWheel {
+        unit: MouseScrollUnit::Line,
+        x: 0.,
+        y: 10.,
+    });
+    for _ in 0..10 {
+        step(&mut app);
+    }
+    assert!(app
+        .world
+        .get::<CameraRigTarget>(rig)
+        .unwrap()
+        .focus
+        .is_some());
+    assert_eq!(fov(&app), before);
+}
+
+#[test]
+fn pixel_snap_shows_rig_on_pixel_grid() {
+    let mut app = test_app();
+    let (mut rig_bundle, camera_bundle) =
+        CameraRigBundle::orthographic(Vec3::new(-10., 10., 0.), 20.);
+    rig_bundle.camera_rig.pixel_snap = Some(0.5);
+    let (right, up) = (
+        camera_bundle.transform.right(),
+        camera_bundle.transform.up(),
+    );
+    let rig = app
+        .world
+        .spawn(rig_bundle)
+        .with_children(|rig| {
+            rig.spawn(camera_bundle);
+        })
+        .id();
+    app.world.send_event(CameraCommand::PanTo {
+        rig: None,
+        target: Vec3::new(1.3, 0., 0.),
+        snap: false,
+    });
+
+    let mut shown = Vec::new();
+    for _ in 0..100 {
+        step(&mut app);
+        shown.push(translation(&app, rig));
+    }
+    // On the grid along the screen, while staying on the ground
+    let on_grid = |value: f32| ((value / 0.5).round() * 0.5 - value).abs() < 0.0001;
+    assert!(shown
+        .iter()
+        .all(|shown| on_grid(shown.dot(right)) && on_grid(shown.dot(up))));
+    assert!(shown.iter().all(|shown| shown.y.abs() < 0.0001));
+    assert!(shown.iter().any(|shown| shown.x != 0.));
+    let last = *shown.last().unwrap();
+    assert!((last.dot(up) - Vec3::new(1.3, 0., 0.).dot(up)).abs() <= 0.25);
+}
+
+#[test]
+fn follow_snap_epsilon_scales_with_world() {
+    let mut app = test_app();
+    // A world measured in kilometres
+    let rig = app
+        .world
+        .spawn(CameraRigBundle {
+            camera_rig: CameraRig {
+                follow_snap_epsilon: 5.,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .id();
+    let hero = Vec3::new(10_000., 0., 0.);
+    app.world.spawn((
+        TransformBundle::from_transform(Transform::from_translation(hero)),
+        CameraRigFollow::new(true),
+    ));
+
+    let mut snapped = None;
+    for update in 0..600 {
+        let before = translation(&app, rig);
+        step(&mut app);
+        let after = translation(&app, rig);
+        if after == hero && snapped.is_none() {
+            // Only jumps the last few units, not while it is still far off
+            assert!(before.distance(hero) <= 10.);
+            snapped = Some(update);
+        }
+    }
+    assert!(snapped.is_some());
+}
+
+#[test]
+fn bounds_mode_keeps_visible_area_in() {
+    // Ground shown after jumping the rig to `x` on a square map of `size`
+    fn shown(bounds_mode: BoundsMode, size: f32, x: f32) -> Rect {
+        let mut app = test_app();
+        let mut rig = CameraRig {
+            bounds_mode,
+            ..Default::default()
+        };
+        rig.set_rect_bounds(Rect::from_center_size(Vec2::ZERO, Vec2::splat(size)));
+        let (rig, camera) = spawn_rig(&mut app, rig, Vec3::new(-75., 75., 0.));
+        *app.world.get_mut::<CameraRigTarget>(rig).unwrap() = CameraRigTarget {
+            rig: Some(Transform::from_xyz(x, 0., 0.)),
+            snap_next_update: true,
+            ..Default::default()
+        };
+        app.update();
+
+        let rig = app.world.get::<Transform>(rig).unwrap();
+        let camera_transform = app.world.get::<Transform>(camera).unwrap();
+        ground_footprint(
+            &rig.mul_transform(*camera_transform).into(),
+            app.world.get::<Projection>(camera).unwrap(),
+            0.,
+        )
+    }
+
+    // Looking past the edge of the map
+    assert!(shown(BoundsMode::RigPoint, 400., 190.).max.x > 200.);
+    let inside = shown(BoundsMode::VisibleArea { clamp_zoom: false }, 400., 190.);
+    assert!(inside.max.x <= 200.01);
+    assert!(inside.min.x > -200.);
+
+    // Zoomed out too far for the map, the view is centered on it
+    let centered = shown(BoundsMode::VisibleArea { clamp_zoom: false }, 100., 190.);
+    assert!(centered.width() > 100.);
+    assert!(centered.center().abs().max_element() < 0.01);
+    // Or zoomed back in until it fits
+    let fitted = shown(BoundsMode::VisibleArea { clamp_zoom: true }, 100., 190.);
+    assert!(fitted.width() <= 100.01);
+    assert!(fitted.height() <= 100.01);
+    assert!(fitted.min.x >= -50.01 && fitted.max.x <= 50.01);
+}
+
+#[test]
+fn sync_smoothing_arrives_together() {
+    // Updates until the rig and camera arrive, with a pan and zoom started together
+    fn arrivals(sync_smoothing: bool) -> (Option<u32>, Option<u32>) {
+        let mut app = test_app();
+        let (rig, camera) = spawn_rig(
+            &mut app,
+            CameraRig {
+                sync_smoothing,
+                ..Default::default()
+            },
+            Vec3::new(-75., 75., 0.),
+        );
+        let camera = *app.world.get::<Transform>(camera).unwrap();
+        let mut target = app.world.get_mut::<CameraRigTarget>(rig).unwrap();
+        target.rig = Some(Transform::from_xyz(20., 0., 0.));
+        target.camera = Some(camera.with_translation(camera.translation * 0.5));
+
+        let (mut rig_done, mut camera_done) = (None, None);
+        for update in 0..200 {
+            step(&mut app);
+            let target = app.world.get::<CameraRigTarget>(rig).unwrap();
+            if target.rig.is_none() && rig_done.is_none() {
+                rig_done = Some(update);
+            }
+            if target.camera.is_none() && camera_done.is_none() {
+                camera_done = Some(update);
+            }
+        }
+        (rig_done, camera_done)
+    }
+
+    let (rig_done, camera_done) = arrivals(true);
+    assert!(rig_done.is_some());
+    assert_eq!(rig_done, camera_done);
+    let (rig_done, camera_done) = arrivals(false);
+    assert_ne!(rig_done, camera_done);
+}
+
+#[test]
+fn zoom_smoothing_eases_distance_separately() {
+    // Camera distance a few updates after zooming from 106 to 50
+    fn distance_after_zoom(zoom_smoothing: Option<f32>) -> f32 {
+        let mut app = test_app();
+        let (_, camera) = spawn_rig(
+            &mut app,
+            CameraRig {
+                zoom_smoothing,
+                ..Default::default()
+            },
+            Vec3::new(-75., 75., 0.),
+        );
+        app.world.send_event(CameraCommand::ZoomTo {
+            distance: 50.,
+            snap: false,
+        });
+        for _ in 0..5 {
+            step(&mut app);
+        }
+        translation(&app, camera).length()
+    }
+
+    let default = distance_after_zoom(None);
+    assert!(default < 100.);
+    assert!(distance_after_zoom(Some(30.)) < default - 10.);
+    assert!(distance_after_zoom(Some(2.)) > default + 10.);
+}
+
+#[test]
+fn spawn_warmup_frames_start_in_place() {
+    let mut app = test_app();
+    let start = Transform::from_xyz(50., 0., 20.);
+    let (rig, _) = spawn_rig(
+        &mut app,
+        CameraRig {
+            spawn_warmup_frames: 2,
+            ..Default::default()
+        },
+        Vec3::new(-75., 75., 0.),
+    );
+    app.world.get_mut::<CameraRigTarget>(rig).unwrap().rig = Some(start);
+    app.update();
+    assert_eq!(*app.world.get::<Transform>(rig).unwrap(), start);
+}
+
+#[test]
+fn use_scaled_time_slows_with_game() {
+    // Distance covered over a few updates of a pan with the game at half speed
+    fn panned(use_scaled_time: bool) -> f32 {
+        let mut app = test_app();
+        app.world.resource_mut::<Time>().set_relative_speed(0.5);
+        let (rig, _) = spawn_rig(
+            &mut app,
+            CameraRig {
+                use_scaled_time,
+                ..Default::default()
+            },
+            Vec3::new(-75., 75., 0.),
+        );
+        app.world.get_mut::<CameraRigTarget>(rig).unwrap().rig =
+            Some(Transform::from_xyz(100., 0., 0.));
+        for _ in 0..3 {
+            step(&mut app);
+        }
+        translation(&app, rig).x
+    }
+
+    assert!(panned(false) > panned(true) * 1.5);
+}
+
+#[test]
+fn shake_decays_on_rig_time_while_paused() {
+    fn trauma_after_pause(use_scaled_time: bool) -> f32 {
+        let mut app = test_app();
+        app.world.resource_mut::<Time>().set_relative_speed(0.);
+        let (_, camera) = spawn_rig(
+            &mut app,
+            CameraRig {
+                use_scaled_time,
+                ..Default::default()
+            },
+            Vec3::new(-75., 75., 0.),
+        );
+        let mut shake = CameraShake::default();
+        shake.add_trauma(1.);
+        app.world.entity_mut(camera).insert(shake);
+        for _ in 0..10 {
+            step(&mut app);
+        }
+        app.world.get::<CameraShake>(camera).unwrap().trauma
+    }
+
+    assert_eq!(trauma_after_pause(true), 1.);
+    assert!(trauma_after_pause(false) < 0.9);
+}
+
+#[test]
+fn keep_on_screen_pans_at_margin() {
+    for follow_dynamics in [
+        FollowDynamics::Exponential,
+        FollowDynamics::critically_damped(40.),
+    ] {
+        let mut app = test_app();
+        let (rig, _) = spawn_rig(
+            &mut app,
+            CameraRig {
+                follow_dynamics,
+                ..Default::default()
+            },
+            Vec3::new(-75., 75., 0.),
+        );
+        let unit = app
+            .world
+            .spawn((
+                TransformBundle::from_transform(Transform::from_xyz(0., 0., 5.)),
+                CameraRigFollow::new(true).with_keep_on_screen_margin(0.1),
+            ))
+            .id();
+        let run = |app: &mut App| {
+            for _ in 0..120 {
+                step(app);
+            }
+            translation(app, rig)
+        };
+
+        // Well within the view, so the rig stays put
+        assert_eq!(run(&mut app), Vec3::ZERO, "{:?}", follow_dynamics);
+
+        // Off the right of the screen, so the rig pans until it is back in view
+        app.world.get_mut::<Transform>(unit).unwrap().translation.z = 60.;
+        let panned = run(&mut app);
+        assert!(panned.z > 20., "{:?}", follow_dynamics);
+        assert!(panned.z < 60., "{:?}", follow_dynamics);
+        assert_eq!(panned.y, 0., "{:?}", follow_dynamics);
+    }
+}
+
+#[test]
+fn lock_to_entity_holds_rig_on_entity() {
+    let mut app = test_app();
+    let (rig, _) = spawn_rig(&mut app, CameraRig::default(), Vec3::new(-75., 75., 0.));
+    app.world.spawn((
+        TransformBundle::from_transform(Transform::from_xyz(-50., 0., 0.)),
+        CameraRigFollow::new(true),
+    ));
+    let cart = app
+        .world
+        .spawn(TransformBundle::from_transform(Transform::from_xyz(
+            10., 0., 0.,
+        )))
+        .id();
+    app.world
+        .get_mut::<CameraRig>(rig)
+        .unwrap()
+        .lock_to_entity(cart);
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::W);
+
+    for update in 0..10 {
+        app.world.get_mut::<Transform>(cart).unwrap().translation.z = update as f32;
+        step(&mut app);
+        assert_eq!(translation(&app, rig), Vec3::new(10., 0., update as f32));
+    }
+
+    // Following picks back up
+    app.world.get_mut::<CameraRig>(rig).unwrap().unlock();
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .release(KeyCode::W);
+    step(&mut app);
+    assert!(translation(&app, rig).x < 10.);
+}
+
+#[test]
+fn zoom_focus_keeps_anchor_on_screen() {
+    let mut app = test_app();
+    let mut camera = Entity::PLACEHOLDER;
+    let rig = app
+        .world
+        .spawn(CameraRigBundle {
+            camera_rig: CameraRig {
+                mouse: MouseConf::default().with_zoom_anchor(ZoomAnchor::GroundCenter),
+                zoom_smoothing: Some(5.),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with_children(|rig| {
+            camera = rig
+                .spawn(Camera3dBundle {
+                    transform: Transform::from_xyz(-10., 20., 5.)
+                        .looking_at(Vec3::new(5., 0., 0.), Vec3::Y),
+                    ..Default::default()
+                })
+                .id();
+        })
+        .id();
+    // Ground at the center of the view
+    let center = |app: &App| {
+        let view = app
+            .world
+            .get::<Transform>(rig)
+            .unwrap()
+            .mul_transform(*app.world.get::<Transform>(camera).unwrap());
+        view.translation - view.forward() * view.translation.y / view.forward().y
+    };
+    let anchor = center(&app);
+
+    app.world.send_event(MouseWheel {
+        unit: MouseScrollUnit::Line,
+        x: 0.,
+        y: 5.,
+    });
+    for _ in 0..120 {
+        step(&mut app);
+        assert!(center(&app).distance(anchor) < 0.01);
+    }
+    let distance = translation(&app, camera).length();
+    assert!((distance - Vec3::new(-10., 20., 5.).length() / 2.).abs() < 0.01);
+}
+
+#[test]
+fn zoom_anchor_holds_with_locked_rig_height() {
+    let mut app = test_app();
+    let (rig, camera) = spawn_rig(
+        &mut app,
+        CameraRig {
+            mouse: MouseConf::default().with_zoom_anchor(ZoomAnchor::GroundCenter),
+            lock_rig_height: Some(10.),
+            ..Default::default()
+        },
+        Vec3::new(-60., 40., 20.),
+    );
+    app.world.get_mut::<Transform>(rig).unwrap().translation = Vec3::new(5., 10., 5.);
+    // Ground at the center of the view
+    let center = |app: &App| {
+        let view = app
+            .world
+            .get::<Transform>(rig)
+            .unwrap()
+            .mul_transform(*app.world.get::<Transform>(camera).unwrap());
+        view.translation - view.forward() * view.translation.y / view.forward().y
+    };
+    step(&mut app);
+    let anchor = center(&app);
+
+    for y in [5., -8.] {
+        app.world.send_event(MouseWheel {
+            unit: MouseScrollUnit::Line,
+            x: 0.,
+            y,
+        });
+        for _ in 0..120 {
+            step(&mut app);
+            assert!(center(&app).distance(anchor) < 0.01);
+            assert_eq!(translation(&app, rig).y, 10.);
+        }
+    }
+}
+
+#[test]
+fn record_and_play_back_path() {
+    let mut app = test_app();
+    let (rig, _) = spawn_rig(&mut app, CameraRig::default(), Vec3::new(-75., 75., 0.));
+    step(&mut app);
+
+    let mut target = app.world.get_mut::<CameraRigTarget>(rig).unwrap();
+    target.record_path(1.);
+    target.rig = Some(Transform::from_xyz(50., 0., 0.));
+    for _ in 0..100 {
+        step(&mut app);
+    }
+    let path = app
+        .world
+        .get_mut::<CameraRigTarget>(rig)
+        .unwrap()
+        .stop_recording()
+        .unwrap();
+    assert!(path.duration() >= 1.);
+    assert!(path.samples.len() > 10);
+
+    // Play it back from the start
+    let start = path.samples[0].rig;
+    app.world
+        .get_mut::<CameraRigTarget>(rig)
+        .unwrap()
+        .playback_path(path);
+    step(&mut app);
+    assert_eq!(*app.world.get::<Transform>(rig).unwrap(), start);
+}
+
+#[test]
+fn stop_holds_rig_and_camera() {
+    let mut app = test_app();
+    let (rig, camera) = spawn_rig(&mut app, CameraRig::default(), Vec3::new(-75., 75., 0.));
+    let city = app
+        .world
+        .spawn(TransformBundle::from_transform(Transform::from_xyz(
+            100., 0., 0.,
+        )))
+        .id();
+    let run = |app: &mut App, updates: usize| {
+        for _ in 0..updates {
+            step(app);
+        }
+        (
+            *app.world.get::<Transform>(rig).unwrap(),
+            *app.world.get::<Transform>(camera).unwrap(),
+        )
+    };
+    app.world
+        .get_mut::<CameraRigTarget>(rig)
+        .unwrap()
+        .focus_on_entity(city, 20., 1.);
+    let (rig_before, camera_before) = run(&mut app, 20);
+    assert!(rig_before.translation.x > 0. && rig_before.translation.x < 100.);
+
+    app.world.get_mut::<CameraRigTarget>(rig).unwrap().stop();
+    assert_eq!(run(&mut app, 60), (rig_before, camera_before));
+
+    // Stopping through the rig ends a shake too
+    let mut shake = CameraShake::default();
+    shake.add_trauma(1.);
+    app.world.entity_mut(camera).insert(shake);
+    let (_, shaking) = run(&mut app, 5);
+    assert!(!shaking
+        .translation
+        .abs_diff_eq(camera_before.translation, 0.001));
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, &app.world);
+    CameraRig::stop(&mut commands, rig);
+    queue.apply(&mut app.world);
+    let (rig_after, camera_after) = run(&mut app, 2);
+    assert_eq!(app.world.get::<CameraShake>(camera).unwrap().trauma, 0.);
+    assert_eq!(rig_after, rig_before);
+    assert!(camera_after
+        .translation
+        .abs_diff_eq(camera_before.translation, 0.0001));
+}
+
+#[test]
+fn speed_multiplier_resets_after_timer() {
+    let mut app = test_app();
+    // A scout ability tripling camera speed for half a second
+    app.insert_resource(CameraRigSpeedMultiplier(3.))
+        .insert_resource(CameraRigSpeedMultiplierReset::from_seconds(0.5));
+
+    let update = |app: &mut App| step_by(app, Duration::from_millis(100));
+    update(&mut app);
+    update(&mut app);
+    assert_eq!(app.world.resource::<CameraRigSpeedMultiplier>().0, 3.);
+    for _ in 0..5 {
+        update(&mut app);
+    }
+    assert_eq!(app.world.resource::<CameraRigSpeedMultiplier>().0, 1.);
+    assert!(!app
+        .world
+        .contains_resource::<CameraRigSpeedMultiplierReset>());
+}
+
+#[test]
+fn orthographic_rig_zooms_and_edge_scrolls() {
+    let mut app = test_app();
+    app.world.spawn((Window::default(), PrimaryWindow));
+    let (mut rig, camera) = CameraRigBundle::orthographic(Vec3::new(-10., 10., 0.), 20.);
+    rig.camera_rig.edge_scroll = Some(EdgeScrollConf::default());
+    let mut camera_entity = Entity::PLACEHOLDER;
+    let rig = app
+        .world
+        .spawn(rig)
+        .with_children(|rig| camera_entity = rig.spawn(camera).id())
+        .id();
+
+    // Push against the right edge and scroll in
+    let mut window = app
+        .world
+        .query_filtered::<&mut Window, With<PrimaryWindow>>()
+        .single_mut(&mut app.world);
+    let right_edge = Vec2::new(window.width() - 1., window.height() / 2.);
+    window.set_cursor_position(Some(right_edge));
+    app.world.send_event(MouseWheel {
+        unit: MouseScrollUnit::Line,
+        x: 0.,
+        y: 0.5,
+    });
+    for _ in 0..10 {
+        step(&mut app);
+    }
+
+    let scale = match app.world.get::<Projection>(camera_entity).unwrap() {
+        Projection::Orthographic(ortho) => ortho.scale,
+        _ => unreachable!(),
+    };
+    assert!(scale < 20.);
+    assert_ne!(translation(&app, rig), Vec3::ZERO);
+}
+
+#[test]
+fn scaled_rig_moves_in_world_units() {
+    fn pan_and_zoom(scale: f32) -> (Vec3, f32) {
+        let mut app = test_app();
+        let (rig, camera) = spawn_rig(
+            &mut app,
+            CameraRig::default(),
+            Vec3::new(-75., 75., 0.) / scale,
+        );
+        app.world.get_mut::<Transform>(rig).unwrap().scale = Vec3::splat(scale);
+        step(&mut app);
+
+        app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::W);
+        app.world.send_event(MouseWheel {
+            unit: MouseScrollUnit::Line,
+            x: 0.,
+            y: 1.,
+        });
+        for _ in 0..30 {
+            step(&mut app);
+        }
+        let distance = app
+            .world
+            .get::<GlobalTransform>(camera)
+            .unwrap()
+            .translation()
+            .distance(translation(&app, rig));
+        (translation(&app, rig), distance)
+    }
+
+    let (position, distance) = pan_and_zoom(1.);
+    let (scaled_position, scaled_distance) = pan_and_zoom(2.);
+    assert_ne!(position, Vec3::ZERO);
+    assert!(scaled_position.abs_diff_eq(position, 0.01));
+    assert!((scaled_distance - distance).abs() < 0.01);
+}
+
+#[test]
+fn keep_in_view_pulls_back_toward_entity() {
+    fn pan_left_with(strength: f32) -> Vec3 {
+        let mut app = test_app();
+        let building = app
+            .world
+            .spawn(TransformBundle::from_transform(Transform::from_xyz(
+                0., 0., 30.,
+            )))
+            .id();
+        let (rig, _) = spawn_rig(&mut app, CameraRig::default(), Vec3::new(-75., 75., 0.));
+        app.world.entity_mut(rig).insert(KeepInView {
+            entity: building,
+            margin: 0.1,
+            strength,
+        });
+        app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::A);
+        for _ in 0..200 {
+            step(&mut app);
+        }
+        translation(&app, rig)
+    }
+
+    // Held back with the building near the right edge of the view
+    let held = pan_left_with(20.);
+    assert!(held.z < 0. && held.z > -10.);
+    // Pushed past a weak pull
+    assert!(pan_left_with(0.1).z < held.z - 10.);
+}
+
+#[test]
+fn toggle_follow_selected_entity() {
+    let mut app = test_app();
+    let rig = CameraRig {
+        gamepad: GamepadConf::standard(),
+        ..Default::default()
+    };
+    spawn_rig(&mut app, rig, Vec3::new(-75., 75., 0.));
+    let unit = app
+        .world
+        .spawn(TransformBundle::from_transform(Transform::from_xyz(
+            50., 0., 0.,
+        )))
+        .id();
+    let press_north = |app: &mut App| {
+        for value in [1., 0.] {
+            press_gamepad_button(app, GamepadButtonType::North, value);
+            app.update();
+        }
+    };
+
+    press_north(&mut app);
+    assert!(app.world.get::<CameraRigFollow>(unit).is_none());
+
+    app.world.resource_mut::<SelectedEntity>().0 = Some(unit);
+    press_north(&mut app);
+    assert_eq!(
+        app.world.get::<CameraRigFollow>(unit),
+        Some(&CameraRigFollow::new(true))
+    );
+    press_north(&mut app);
+    assert_eq!(
+        app.world.get::<CameraRigFollow>(unit),
+        Some(&CameraRigFollow::new(false))
+    );
+}
+
+#[test]
+fn blend_moves_view_between_rigs() {
+    let mut app = test_app();
+    let mut spawn_rig_at = |x: f32| {
+        let (rig, camera) = spawn_rig(&mut app, CameraRig::default(), Vec3::new(-75., 75., 0.));
+        app.world.get_mut::<Transform>(rig).unwrap().translation.x = x;
+        (rig, camera)
+    };
+    let (strategic, _) = spawn_rig_at(0.);
+    let (tactical, tactical_camera) = spawn_rig_at(100.);
+
+    app.world.resource_mut::<CameraBlend>().blend(
+        strategic,
+        tactical,
+        Duration::from_millis(500),
+        SmootherKind::SmoothStep,
+    );
+    let mut finished = app
+        .world
+        .resource::<Events<CameraBlendFinished>>()
+        .get_reader();
+    let mut camera_x = Vec::new();
+    let mut finished_on = None;
+    for update in 0..40 {
+        step(&mut app);
+        let camera = app.world.get::<GlobalTransform>(tactical_camera).unwrap();
+        camera_x.push(camera.translation().x);
+        let events = app.world.resource::<Events<CameraBlendFinished>>();
+        if finished.iter(events).next().is_some() {
+            finished_on = Some(update);
+        }
+    }
+
+    // The view starts over the strategic rig and ends over the tactical one
+    assert!(camera_x[0] < 0.);
+    assert!(camera_x[15] > camera_x[0] && camera_x[15] < 25.);
+    assert_eq!(camera_x[39], 25.);
+    assert!(finished_on.is_some());
+}
+
+#[test]
+fn shake_seed_repeats_pattern() {
+    // Offsets for each update of a shake, starting `start_ms` into the app
+    fn shake_offsets(seed: u64, start_ms: u64) -> Vec<Vec3> {
+        let mut app = test_app();
+        let camera = app
+            .world
+            .spawn((TransformBundle::default(), CameraShake::default()))
+            .id();
+        step_by(&mut app, Duration::from_millis(start_ms));
+        let mut shake = app.world.get_mut::<CameraShake>(camera).unwrap();
+        shake.seed = seed;
+        shake.add_trauma(1.);
+        (0..30)
+            .map(|_| {
+                step(&mut app);
+                translation(&app, camera)
+            })
+            .collect()
+    }
+
+    let offsets = shake_offsets(7, 16);
+    assert!(offsets.iter().any(|offset| *offset != Vec3::ZERO));
+    assert_eq!(shake_offsets(7, 16), offsets);
+    assert_eq!(shake_offsets(7, 5000), offsets);
+    assert_ne!(shake_offsets(8, 16), offsets);
+    // The high bits count too
+    assert_ne!(shake_offsets(1 << 32 | 1, 16), shake_offsets(0, 16));
+}
+
+#[test]
+fn set_shake_seed_reaches_rig_camera() {
+    let mut app = test_app();
+    let (rig, camera) = spawn_rig(&mut app, CameraRig::default(), Vec3::new(-75., 75., 0.));
+    app.world.entity_mut(camera).insert(CameraShake::default());
+
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, &app.world);
+    CameraRig::set_shake_seed(&mut commands, rig, 9);
+    queue.apply(&mut app.world);
+    assert_eq!(app.world.get::<CameraShake>(camera).unwrap().seed, 9);
+}
+
+#[test]
+fn follow_reactivation_timer_ticks_once_per_update() {
+    let mut app = test_app();
+    let (rig, _) = spawn_rig(
+        &mut app,
+        CameraRig {
+            follow_activation_delay_secs: 10.,
+            ..Default::default()
+        },
+        Vec3::new(-75., 75., 0.),
+    );
+    app.world.spawn((
+        TransformBundle::from_transform(Transform::from_xyz(50., 0., 0.)),
+        CameraRigFollow::new(true),
+    ));
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::W);
+    step(&mut app);
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .release(KeyCode::W);
+    step(&mut app);
+
+    let elapsed = |app: &App| {
+        app.world
+            .get::<CameraRigTarget>(rig)
+            .unwrap()
+            .follow_reactivation_timer
+            .as_ref()
+            .unwrap()
+            .elapsed()
+    };
+    let start = elapsed(&app);
+    for _ in 0..10 {
+        step(&mut app);
+    }
+    assert_eq!(elapsed(&app) - start, Duration::from_millis(160));
+}
+
+#[test]
+fn follow_keeps_zoom_and_pitch() {
+    let mut app = test_app();
+    let offset = Vec3::new(-75., 75., 0.);
+    let (rig, camera) = spawn_rig(&mut app, CameraRig::default(), offset);
+    let cube = app
+        .world
+        .spawn((TransformBundle::default(), CameraRigFollow::new(true)))
+        .id();
+    let sensitivity = MouseConf::default().zoom_sensitivity;
+
+    let mut expected = offset.length();
+    for frame in 0..1000 {
+        app.world.get_mut::<Transform>(cube).unwrap().translation.x = frame as f32 * 0.5;
+        if frame % 100 == 0 && frame < 900 {
+            let y = if frame % 300 == 0 { -1. } else { 1. };
+            app.world.send_event(MouseWheel {
+                unit: MouseScrollUnit::Line,
+                x: 0.,
+                y,
+            });
+            expected -= expected * y * sensitivity;
+        }
+        step(&mut app);
+        let pitch = camera_pitch(translation(&app, camera));
+        assert!((pitch - camera_pitch(offset)).abs() < 0.0001);
+    }
+    assert!((translation(&app, camera).length() - expected).abs() < 0.01);
+    assert!(translation(&app, rig).x > 400.);
+}
+
+#[test]
+fn free_fly_keeps_entry_speed() {
+    let mut app = test_app();
+    let (_, camera) = spawn_rig(
+        &mut app,
+        CameraRig {
+            camera_mode: CameraMode::FreeFly,
+            ..Default::default()
+        },
+        Vec3::new(-75., 75., 0.),
+    );
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::S);
+    let mut moved = Vec::new();
+    for _ in 0..100 {
+        let before = translation(&app, camera);
+        step(&mut app);
+        moved.push(translation(&app, camera).distance(before));
+    }
+    let speed = KeyboardConf::default().move_speed(Vec3::new(-75., 75., 0.).length());
+    assert!(moved[1..]
+        .iter()
+        .all(|moved| (moved - speed).abs() < 0.0001));
+}
+
+#[test]
+fn free_fly_leaves_wheel_for_other_rigs() {
+    let mut app = test_app();
+    let offset = Vec3::new(-75., 75., 0.);
+    spawn_rig(
+        &mut app,
+        CameraRig {
+            camera_mode: CameraMode::FreeFly,
+            ..Default::default()
+        },
+        offset,
+    );
+    let (_, camera) = spawn_rig(&mut app, CameraRig::default(), offset);
+    app.world.send_event(MouseWheel {
+        unit: MouseScrollUnit::Line,
+        x: 0.,
+        y: 1.,
+    });
+    for _ in 0..10 {
+        step(&mut app);
+    }
+    assert!(translation(&app, camera).length() < offset.length());
+}
+
+#[test]
+fn allow_flags_gate_orbit_and_free_fly() {
+    let mut keys = Input::default();
+    keys.press(KeyCode::D);
+    let buttons = Input::default();
+    let orbit_yaw = |keyboard: KeyboardConf| {
+        let rig = CameraRig {
+            camera_mode: CameraMode::Orbit(OrbitConf::default()),
+            keyboard,
+            ..Default::default()
+        };
+        let input = CameraRigInput::new(&rig.keyboard, &rig.mouse, &keys, &buttons);
+        compute_rig_delta(&rig, &CameraRigPose::default(), &input)
+            .0
+            .rotation
+            .to_euler(EulerRot::YXZ)
+            .0
+    };
+    // A and D turn an orbiting rig, so follow `allow_rotate`
+    assert_ne!(
+        orbit_yaw(KeyboardConf::default().with_allow_strafe(false)),
+        0.
+    );
+    assert_eq!(
+        orbit_yaw(KeyboardConf::default().with_allow_rotate(false)),
+        0.
+    );
+
+    let mut app = test_app();
+    let (_, camera) = spawn_rig(
+        &mut app,
+        CameraRig {
+            camera_mode: CameraMode::FreeFly,
+            keyboard: KeyboardConf::default()
+                .with_allow_pan(false)
+                .with_allow_rotate(false),
+            ..Default::default()
+        },
+        Vec3::new(-75., 75., 0.),
+    );
+    let mut keys = app.world.resource_mut::<Input<KeyCode>>();
+    keys.press(KeyCode::W);
+    keys.press(KeyCode::E);
+    step(&mut app);
+    let start = translation(&app, camera);
+    for _ in 0..10 {
+        step(&mut app);
+    }
+    assert_eq!(translation(&app, camera), start);
+
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::D);
+    for _ in 0..10 {
+        step(&mut app);
+    }
+    assert_ne!(translation(&app, camera), start);
+}
+
+#[test]
+fn orbit_keys_turn_within_limits() {
+    let mut app = test_app();
+    let limits = OrbitConf {
+        min_polar: 0.3,
+        max_polar: 1.2,
+        ..Default::default()
+    };
+    let (rig_bundle, camera_bundle) = OrbitCameraRigBundle::new(50., 0.8, 0.5, limits);
+    let mut camera = Entity::PLACEHOLDER;
+    let rig = app
+        .world
+        .spawn(rig_bundle)
+        .with_children(|rig| camera = rig.spawn(camera_bundle).id())
+        .id();
+    let polar = |app: &App| translation(app, camera).angle_between(Vec3::Y);
+    let azimuth = |app: &App| {
+        let rotation = app.world.get::<Transform>(rig).unwrap().rotation;
+        rotation.to_euler(EulerRot::YXZ).0
+    };
+    let hold = |app: &mut App, key: KeyCode| {
+        app.world.resource_mut::<Input<KeyCode>>().press(key);
+        for _ in 0..120 {
+            step(app);
+        }
+        app.world.resource_mut::<Input<KeyCode>>().release(key);
+    };
+
+    // Spawned in place rather than easing there
+    step(&mut app);
+    assert!((polar(&app) - 0.8).abs() < 0.001);
+    assert!((translation(&app, camera).length() - 50.).abs() < 0.001);
+    assert!((azimuth(&app) - 0.5).abs() < 0.001);
+
+    hold(&mut app, KeyCode::W);
+    assert!((polar(&app) - 0.3).abs() < 0.01);
+    hold(&mut app, KeyCode::S);
+    assert!((polar(&app) - 1.2).abs() < 0.01);
+    assert!((azimuth(&app) - 0.5).abs() < 0.001);
+
+    hold(&mut app, KeyCode::D);
+    let turned = azimuth(&app);
+    assert!((turned - 0.5).abs() > 0.1);
+    hold(&mut app, KeyCode::A);
+    assert!((azimuth(&app) - 0.5).abs() < (turned - 0.5).abs());
+    assert!((polar(&app) - 1.2).abs() < 0.01);
+}
+
+/// The rig and camera after one update of `input`, for a rig at the origin
+/// with `camera` under it
+fn rig_after(rig: &CameraRig, camera: Transform, input: &CameraRigInput) -> (Transform, Transform) {
+    let pose = CameraRigPose {
+        camera: Some(camera),
+        ..Default::default()
+    };
+    compute_rig_delta(rig, &pose, input)
+}
+
+#[test]
+fn mouse_rotate_distance_scale_slows_when_zoomed_out() {
+    let rig = CameraRig {
+        mouse: MouseConf::default().with_rotate_distance_scale(-0.001, 1.1),
+        ..Default::default()
+    };
+    let keys = Input::default();
+    let mut buttons = Input::default();
+    buttons.press(MouseButton::Right);
+    let input = CameraRigInput {
+        mouse_motion: Vec2::new(100., 0.),
+        ..CameraRigInput::new(&rig.keyboard, &rig.mouse, &keys, &buttons)
+    };
+    let yaw_at = |distance: f32| {
+        let camera = Transform::from_translation(Vec3::new(-1., 1., 0.).normalize() * distance)
+            .looking_at(Vec3::ZERO, Vec3::Y);
+        let (rig, _) = rig_after(&rig, camera, &input);
+        rig.rotation.to_euler(EulerRot::YXZ).0
+    };
+
+    let near = yaw_at(100.);
+    assert!(near.abs() > 0.01);
+    assert!((yaw_at(600.) / near - 0.5).abs() < 0.001);
+    // Past where the scale reaches zero it stays stopped rather than reversing
+    assert_eq!(yaw_at(2000.), 0.);
+}
+
+#[test]
+fn over_zoom_tilt_pitches_at_min_distance() {
+    let rig = CameraRig {
+        mouse: MouseConf::default()
+            .with_zoom_limits(10., 100.)
+            .with_over_zoom_tilt(0.5, 1.4),
+        ..Default::default()
+    };
+    let (keys, buttons) = (Input::default(), Input::default());
+    let input = CameraRigInput {
+        mouse_wheel: &[1.],
+        ..CameraRigInput::new(&rig.keyboard, &rig.mouse, &keys, &buttons)
+    };
+
+    let mut camera = Transform::from_xyz(-10., 10., 0.).looking_at(Vec3::ZERO, Vec3::Y);
+    camera.translation = camera.translation.normalize() * 10.;
+    let mut pitches = vec![camera_pitch(camera.translation)];
+    for _ in 0..20 {
+        camera = rig_after(&rig, camera, &input).1;
+        assert!((camera.translation.length() - 10.).abs() < 0.0001);
+        pitches.push(camera_pitch(camera.translation));
+    }
+    assert!(pitches[1] > pitches[0]);
+    assert!(pitches.windows(2).all(|pair| pair[1] >= pair[0]));
+    assert!((pitches[20] - 1.4).abs() < 0.0001);
+}
+
+#[test]
+fn lock_rig_height_keeps_rotated_rig_level() {
+    // Panning along the rig's own pitched axes
+    let tilted = CameraRig {
+        map_forward: Some(Vec3::X),
+        pan_ignores_pitch: false,
+        stabilize_roll: false,
+        ..Default::default()
+    };
+    let locked = CameraRig {
+        lock_rig_height: Some(5.),
+        ..tilted.clone()
+    };
+    let pose = CameraRigPose {
+        rig: Transform::from_xyz(0., 5., 0.).with_rotation(Quat::from_euler(
+            EulerRot::YXZ,
+            0.7,
+            -0.4,
+            0.,
+        )),
+        camera: Some(Transform::from_xyz(-10., 10., 0.).looking_at(Vec3::ZERO, Vec3::Y)),
+        ..Default::default()
+    };
+    let mut keys = Input::default();
+    keys.press(KeyCode::W);
+    keys.press(KeyCode::D);
+    let buttons = Input::default();
+
+    let height = |rig: &CameraRig| {
+        let input = CameraRigInput::new(&rig.keyboard, &rig.mouse, &keys, &buttons);
+        compute_rig_delta(rig, &pose, &input).0.translation.y
+    };
+    assert_ne!(height(&tilted), 5.);
+    assert_eq!(height(&locked), 5.);
+}
+
+#[test]
+fn fine_move_modifier_slows_panning() {
+    let rig = CameraRig {
+        keyboard: KeyboardConf::default().with_fine_move_modifier([KeyCode::LControl]),
+        ..Default::default()
+    };
+    let camera = Transform::from_xyz(-75., 75., 0.).looking_at(Vec3::ZERO, Vec3::Y);
+    let buttons = Input::default();
+    let panned = |keys: &[KeyCode]| {
+        let mut input = Input::default();
+        for key in keys {
+            input.press(*key);
+        }
+        let input = CameraRigInput::new(&rig.keyboard, &rig.mouse, &input, &buttons);
+        rig_after(&rig, camera, &input).0.translation.length()
+    };
+
+    let normal = panned(&[KeyCode::W]);
+    let fine = panned(&[KeyCode::W, KeyCode::LControl]);
+    assert!(normal > 0.);
+    assert!((fine - normal * rig.keyboard.fine_move_multiplier).abs() < 0.0001);
+}
+
+#[test]
+fn keyboard_rotation_settles_after_release() {
+    let mut app = test_app();
+    let (rig, _) = spawn_rig(&mut app, CameraRig::default(), Vec3::new(-75., 75., 0.));
+    let rotation = |app: &App| app.world.get::<Transform>(rig).unwrap().rotation;
+
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::Q);
+    for update in 0..125 {
+        step(&mut app);
+        if update == 30 {
+            assert!(rotation(&app).angle_between(Quat::IDENTITY) > 0.5);
+        }
+    }
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .release(KeyCode::Q);
+    let updates = (3. * SMOOTHING_SECS / 0.016).ceil() as usize;
+    for _ in 0..updates {
+        step(&mut app);
+    }
+    let settled = rotation(&app);
+    for _ in 0..120 {
+        step(&mut app);
+    }
+    assert!(settled.angle_between(rotation(&app)) < 0.05);
+}
+
+#[test]
+fn input_context_disables_rotation_until_popped() {
+    let mut app = test_app();
+    let (rig, _) = spawn_rig(&mut app, CameraRig::default(), Vec3::new(-75., 75., 0.));
+    let rotation = |app: &App| app.world.get::<Transform>(rig).unwrap().rotation;
+    app.world
+        .resource_mut::<CameraInputContexts>()
+        .push_context(CameraInputContext {
+            keyboard: Some(KeyboardConf {
+                allow_rotate: false,
+                ..Default::default()
+            }),
+            mouse: None,
+        });
+
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::Q);
+    for _ in 0..30 {
+        step(&mut app);
+    }
+    assert_eq!(rotation(&app), Quat::IDENTITY);
+
+    app.world
+        .resource_mut::<CameraInputContexts>()
+        .pop_context();
+    for _ in 0..30 {
+        step(&mut app);
+    }
+    assert_ne!(rotation(&app), Quat::IDENTITY);
+}
+
+#[test]
+fn orbit_pivot_keeps_center_ground_in_place() {
+    let rig = CameraRig {
+        mouse: MouseConf {
+            orbit_pivot: OrbitPivot::ScreenCenterGround,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    // Looking at the ground 5 units in front of the rig
+    let pivot = Vec3::new(5., 0., 0.);
+    let camera = Transform::from_xyz(-10., 10., 0.).looking_at(pivot, Vec3::Y);
+    let keys = Input::default();
+    let mut buttons = Input::default();
+    buttons.press(MouseButton::Right);
+    let input = CameraRigInput {
+        mouse_motion: Vec2::new(200., 0.),
+        ..CameraRigInput::new(&rig.keyboard, &rig.mouse, &keys, &buttons)
+    };
+
+    let (rig_transform, camera_transform) = rig_after(&rig, camera, &input);
+    let view = rig_transform.mul_transform(camera_transform);
+    let center = view.translation - view.forward() * view.translation.y / view.forward().y;
+    assert!(center.distance(pivot) < 0.001);
+    assert_ne!(rig_transform.rotation, Quat::IDENTITY);
+    // Swung around the pivot rather than turned on the spot
+    assert!(rig_transform.translation.length() > 0.1);
+    assert!((rig_transform.translation.distance(pivot) - 5.).abs() < 0.001);
+}
+
+#[test]
+fn fov_zoom_eases_over_several_updates() {
+    let mut app = test_app();
+    let mut camera = Entity::PLACEHOLDER;
+    app.world
+        .spawn(CameraRigBundle {
+            camera_rig: CameraRig {
+                mouse: MouseConf {
+                    fov_zoom: Some(FovZoomConf::default()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with_children(|rig| {
+            camera = rig
+                .spawn(Camera3dBundle {
+                    transform: Transform::from_xyz(-75., 75., 0.).looking_at(Vec3::ZERO, Vec3::Y),
+                    projection: Projection::Perspective(PerspectiveProjection {
+                        fov: 0.4,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+                .id();
+        });
+    step(&mut app);
+    let fov = |app: &App| match app.world.get::<Projection>(camera).unwrap() {
+        Projection::Perspective(perspective) => perspective.fov,
+        _ => unreachable!(),
+    };
+
+    app.world.send_event(MouseWheel {
+        unit: MouseScrollUnit::Line,
+        x: 0.,
+        y: 10.,
+    });
+    let mut fovs = Vec::new();
+    for _ in 0..60 {
+        step(&mut app);
+        fovs.push(fov(&app));
+    }
+    let target = 0.4 - 10. * FovZoomConf::default().sensitivity;
+    assert!(fovs[0] < 0.4 && fovs[0] > target + 0.01);
+    assert!(fovs[2] > target + 0.001);
+    assert!(fovs.windows(2).all(|pair| pair[1] <= pair[0]));
+    assert!((fovs[59] - target).abs() < 0.0001);
+}
+
+#[test]
+fn world_to_screen_projects_known_pose() {
+    let mut app = test_app();
+    app.add_plugin(AssetPlugin::default())
+        .add_asset::<Image>()
+        .add_plugin(CameraProjectionPlugin::<Projection>::default());
+    let window = app
+        .world
+        .spawn((
+            Window {
+                resolution: WindowResolution::new(800., 600.),
+                ..Default::default()
+            },
+            PrimaryWindow,
+        ))
+        .id();
+    let camera = app
+        .world
+        .spawn(Camera3dBundle {
+            transform: Transform::from_xyz(0., 0., 10.).looking_at(Vec3::ZERO, Vec3::Y),
+            ..Default::default()
+        })
+        .id();
+    app.update();
+
+    let window = app.world.get::<Window>(window).unwrap();
+    let camera_transform = app.world.get::<GlobalTransform>(camera).unwrap();
+    let camera = app.world.get::<Camera>(camera).unwrap();
+    let screen = |world: Vec3| CameraRig::world_to_screen(world, camera, camera_transform, window);
+
+    // Straight ahead is the middle of the window
+    let center = screen(Vec3::ZERO).unwrap();
+    assert!(center.distance(Vec2::new(400., 300.)) < 0.001);
+    // At the top edge of the default 45 degree fov
+    let top = screen(Vec3::new(0., 10. * (PI / 8.).tan(), 0.)).unwrap();
+    assert!(top.distance(Vec2::new(400., 600.)) < 0.01);
+    assert_eq!(screen(Vec3::new(0., 0., 20.)), None);
+}
+
+#[test]
+fn drag_threshold_ignores_small_moves() {
+    let mut app = test_app();
+    let (rig, _) = spawn_rig(
+        &mut app,
+        CameraRig {
+            mouse: MouseConf::default().with_drag_threshold(10.),
+            ..Default::default()
+        },
+        Vec3::new(-75., 75., 0.),
+    );
+    app.world
+        .resource_mut::<Input<MouseButton>>()
+        .press(MouseButton::Left);
+    let drag = |app: &mut App, delta: Vec2| {
+        app.world.send_event(MouseMotion { delta });
+        for _ in 0..30 {
+            step(app);
+        }
+        translation(app, rig)
+    };
+
+    // A click with a shaky hand
+    assert_eq!(drag(&mut app, Vec2::new(3., 0.)), Vec3::ZERO);
+    assert_eq!(drag(&mut app, Vec2::new(0., -4.)), Vec3::ZERO);
+    // Carrying on past the threshold
+    assert_ne!(drag(&mut app, Vec2::new(20., 0.)), Vec3::ZERO);
+}
+
+#[test]
+fn long_spins_keep_rotation_normalized() {
+    let mut app = test_app();
+    let (rig, camera) = spawn_rig(&mut app, CameraRig::default(), Vec3::new(-75., 75., 0.));
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::Q);
+    app.world
+        .resource_mut::<Input<MouseButton>>()
+        .press(MouseButton::Right);
+    let mut distances = Vec::new();
+    for update in 0..5000 {
+        app.world.send_event(MouseMotion {
+            delta: Vec2::new(37., if update % 2 == 0 { 5. } else { -5. }),
+        });
+        step(&mut app);
+        if update % 1000 == 999 {
+            distances.push(translation(&app, camera).length());
+        }
+    }
+
+    let rig = app.world.get::<Transform>(rig).unwrap().rotation;
+    assert!((rig.length() - 1.).abs() < 0.00001);
+    assert!(rig.mul_vec3(Vec3::Y).distance(Vec3::Y) < 0.0001);
+    // Rounding stays small as the spins go on
+    let start = Vec3::new(-75., 75., 0.).length();
+    assert!(distances
+        .iter()
+        .all(|distance| (distance - start).abs() < 0.1));
+    let camera = app.world.get::<GlobalTransform>(camera).unwrap();
+    assert!(camera.right().y.abs() < 0.0001);
+}
+
+#[test]
+fn edge_scroll_ramps_between_margins() {
+    let conf = EdgeScrollConf::default();
+    let size = Vec2::new(800., 600.);
+    let push = |conf: &EdgeScrollConf, x: f32, y: f32| conf.push(Vec2::new(x, y), size);
+
+    // Full speed in the outer band, nothing inside the inner one
+    assert_eq!(push(&conf, 795., 300.), Vec2::X);
+    assert_eq!(push(&conf, 5., 300.), -Vec2::X);
+    assert_eq!(push(&conf, 400., 595.), Vec2::Y);
+    assert_eq!(push(&conf, 760., 300.), Vec2::ZERO);
+    assert_eq!(push(&conf, 400., 300.), Vec2::ZERO);
+
+    // Halfway between the margins, shaped by the curve
+    assert!((push(&conf, 775., 300.).x - 0.25).abs() < 0.0001);
+    let linear = EdgeScrollConf { curve: 1., ..conf };
+    assert!((push(&linear, 775., 300.).x - 0.5).abs() < 0.0001);
+    assert!((push(&linear, 25., 300.).x + 0.5).abs() < 0.0001);
+
+    // Corners combine both axes before normalizing
+    let corner = push(&conf, 795., 595.);
+    assert!((corner - Vec2::ONE.normalize()).length() < 0.0001);
+    let partial = push(&linear, 795., 575.);
+    assert!((partial - Vec2::new(1., 0.5).normalize()).length() < 0.0001);
+    assert!((push(&linear, 775., 575.) - Vec2::splat(0.5)).length() < 0.0001);
+}
+
+#[test]
+fn edge_scroll_speed_follows_camera_distance() {
+    let panned = |rig: &CameraRig, distance: f32| {
+        let (keys, buttons) = (Input::default(), Input::default());
+        let input = CameraRigInput {
+            window_size: Some(Vec2::new(800., 600.)),
+            cursor: CursorState::new(Some(Vec2::new(799., 300.)), None),
+            ..CameraRigInput::new(&rig.keyboard, &rig.mouse, &keys, &buttons)
+        };
+        let camera = Transform::from_translation(Vec3::new(-1., 1., 0.).normalize() * distance)
+            .looking_at(Vec3::ZERO, Vec3::Y);
+        rig_after(rig, camera, &input).0.translation.length()
+    };
+    let rig = CameraRig {
+        edge_scroll: Some(EdgeScrollConf::default()),
+        ..Default::default()
+    };
+    let (m, c) = EdgeScrollConf::default().sensitivity;
+    assert!((panned(&rig, 100.) - (100. * m + c)).abs() < 0.0001);
+    assert!((panned(&rig, 1000.) - (1000. * m + c)).abs() < 0.0001);
+
+    // Capped like the keys, a second's worth each update at most
+    let capped = CameraRig {
+        keyboard: KeyboardConf::default().with_max_pan_speed(6.),
+        ..rig
+    };
+    assert!((panned(&capped, 1000.) - 0.1).abs() < 0.0001);
+}
+
+#[test]
+fn drag_follows_the_screen_at_any_yaw() {
+    let rig = CameraRig::default();
+    let (keys, mut buttons) = (Input::default(), Input::default());
+    buttons.press(MouseButton::Left);
+    let drag = |camera: Transform, drag_motion: Vec2| {
+        let input = CameraRigInput {
+            mouse_motion: drag_motion,
+            drag_motion,
+            ..CameraRigInput::new(&rig.keyboard, &rig.mouse, &keys, &buttons)
+        };
+        rig_after(&rig, camera, &input).0.translation
+    };
+
+    for yaw in [0., PI / 2., 0.8, PI, -PI / 2., 4.] {
+        let offset = Quat::from_rotation_y(yaw).mul_vec3(Vec3::new(-75., 75., 0.));
+        let camera = Transform::from_translation(offset).looking_at(Vec3::ZERO, Vec3::Y);
+        let right = camera.right().normalize();
+        let forward = Vec3::new(-offset.x, 0., -offset.z).normalize();
+
+        // Grabbing the ground moves the rig against the drag
+        let moved = drag(camera, Vec2::new(10., 0.));
+        assert!(moved.y.abs() < 0.0001);
+        assert!(moved.normalize().distance(-right) < 0.0001);
+        let moved = drag(camera, Vec2::new(0., 10.));
+        assert!(moved.y.abs() < 0.0001);
+        assert!(moved.normalize().distance(forward) < 0.0001);
+    }
+
+    // The old mapping ignores the camera
+    let rig = CameraRig {
+        mouse: MouseConf::default().with_legacy_drag_mapping(true),
+        ..Default::default()
+    };
+    let input = CameraRigInput {
+        mouse_motion: Vec2::new(0., 10.),
+        drag_motion: Vec2::new(0., 10.),
+        ..CameraRigInput::new(&rig.keyboard, &rig.mouse, &keys, &buttons)
+    };
+    let camera = Transform::from_xyz(0., 75., -75.).looking_at(Vec3::ZERO, Vec3::Y);
+    let moved = rig_after(&rig, camera, &input).0.translation;
+    assert!(moved.normalize().distance(Vec3::X) < 0.0001);
+}
+
+#[test]
+fn stabilize_roll_keeps_right_horizontal() {
+    let (keys, mut buttons) = (Input::default(), Input::default());
+    buttons.press(MouseButton::Right);
+    let rotate_many = |rig: &CameraRig| {
+        // Start out with a little roll on both
+        let mut camera = Transform::from_xyz(-75., 75., 0.).looking_at(Vec3::ZERO, Vec3::Y);
+        camera.rotate_local_z(0.1);
+        let mut pose = CameraRigPose {
+            rig: Transform::from_rotation(Quat::from_rotation_x(0.05)),
+            camera: Some(camera),
+            ..Default::default()
+        };
+        for update in 0..500 {
+            let mouse_motion = Vec2::new(23., if update % 3 == 0 { 9. } else { -4. });
+            let input = CameraRigInput {
+                mouse_motion,
+                ..CameraRigInput::new(&rig.keyboard, &rig.mouse, &keys, &buttons)
+            };
+            let (rig_transform, camera) = compute_rig_delta(rig, &pose, &input);
+            pose.rig = rig_transform;
+            pose.camera = Some(camera);
+        }
+        pose.rig.mul_transform(pose.camera.unwrap()).right()
+    };
+
+    assert!(rotate_many(&CameraRig::default()).y.abs() < 0.0001);
+    let unstabilized = CameraRig {
+        stabilize_roll: false,
+        ..Default::default()
+    };
+    assert!(rotate_many(&unstabilized).y.abs() > 0.01);
+}
+
+#[test]
+fn keyboard_pans_toward_camera_view() {
+    let mut app = test_app();
+    let (rig, _) = spawn_rig(&mut app, CameraRig::default(), Vec3::new(0., 75., -75.));
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::W);
+    for _ in 0..30 {
+        step(&mut app);
+    }
+
+    // The camera sits at -Z looking toward +Z
+    let moved = translation(&app, rig);
+    assert!(moved.z > 1.);
+    assert!(moved.x.abs() < 0.0001 && moved.y.abs() < 0.0001);
+
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .release(KeyCode::W);
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::D);
+    for _ in 0..30 {
+        step(&mut app);
+    }
+    // Looking down +Z puts screen right at -X
+    assert!(translation(&app, rig).x < -1.);
+}
+
+#[test]
+fn spring_follow_overshoots_then_settles() {
+    // Furthest the rig got and where it ended up following an entity at 100
+    fn follow(follow_dynamics: FollowDynamics) -> (f32, Vec3) {
+        let mut app = test_app();
+        let rig = app
+            .world
+            .spawn(CameraRigBundle {
+                camera_rig: CameraRig {
+                    follow_dynamics,
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .id();
+        app.world.spawn((
+            TransformBundle::from_transform(Transform::from_xyz(100., 0., 0.)),
+            CameraRigFollow::new(true),
+        ));
+        let mut furthest = 0f32;
+        for _ in 0..600 {
+            step(&mut app);
+            furthest = furthest.max(translation(&app, rig).x);
+        }
+        (furthest, translation(&app, rig))
+    }
+
+    let (furthest, settled) = follow(FollowDynamics::Spring {
+        stiffness: 100.,
+        damping: 8.,
+    });
+    assert!(furthest > 101. && furthest < 130.);
+    assert_eq!(settled, Vec3::new(100., 0., 0.));
+
+    let (furthest, settled) = follow(FollowDynamics::critically_damped(100.));
+    assert!(furthest <= 100.);
+    assert_eq!(settled, Vec3::new(100., 0., 0.));
+}
+
+#[test]
+fn zoom_level_changes_once_per_threshold() {
+    let mut app = test_app();
+    let (rig, camera) = spawn_rig(
+        &mut app,
+        CameraRig {
+            zoom_level_thresholds: vec![50., 150.],
+            ..Default::default()
+        },
+        Vec3::new(-75., 75., 0.),
+    );
+    let mut changes = app
+        .world
+        .resource::<Events<ZoomLevelChanged>>()
+        .get_reader();
+    step(&mut app);
+
+    // From about 106 to 42, easing in over many updates
+    app.world.send_event(MouseWheel {
+        unit: MouseScrollUnit::Line,
+        x: 0.,
+        y: 6.,
+    });
+    let mut sent = Vec::new();
+    for _ in 0..120 {
+        step(&mut app);
+        let events = app.world.resource::<Events<ZoomLevelChanged>>();
+        sent.extend(changes.iter(events).copied());
+    }
+    assert!(translation(&app, camera).length() < 50.);
+    assert_eq!(sent, vec![ZoomLevelChanged { rig, level: 0 }]);
+}
+
+#[test]
+fn drag_modifier_takes_shared_button() {
+    // Right drag rotates and shift right drag pans
+    let mouse = MouseConf::default()
+        .with_rotate([MouseButton::Right])
+        .with_drag([MouseButton::Right])
+        .with_drag_modifier([KeyCode::LShift]);
+    let mut buttons = Input::default();
+    buttons.press(MouseButton::Right);
+    let mut keys = Input::default();
+    assert_eq!(mouse.gesture(&buttons, &keys), Some(MouseGesture::Rotate));
+    keys.press(KeyCode::LShift);
+    assert_eq!(mouse.gesture(&buttons, &keys), Some(MouseGesture::Drag));
+
+    // With both set the rotate modifier still wins
+    let mouse = mouse.with_rotate_modifier([KeyCode::LShift]);
+    assert_eq!(mouse.gesture(&buttons, &keys), Some(MouseGesture::Rotate));
+}
+
+#[test]
+fn gamepad_waits_for_animation() {
+    let mut app = test_app();
+    let mut gamepad = GamepadConf::default();
+    gamepad
+        .button_bindings
+        .insert(CameraAction::ResetView, vec![GamepadButtonType::South]);
+    let (rig, _) = spawn_rig(
+        &mut app,
+        CameraRig {
+            gamepad,
+            disable_input_during_animation: true,
+            ..Default::default()
+        },
+        Vec3::new(-75., 75., 0.),
+    );
+    let castle = app
+        .world
+        .spawn(TransformBundle::from_transform(Transform::from_xyz(
+            100., 0., 0.,
+        )))
+        .id();
+    app.world
+        .get_mut::<CameraRigTarget>(rig)
+        .unwrap()
+        .focus_on_entity(castle, 50., 10.);
+    let mut commands = app.world.resource::<Events<CameraCommand>>().get_reader();
+    let mut resets = |app: &mut App| {
+        for value in [1., 0.] {
+            press_gamepad_button(app, GamepadButtonType::South, value);
+            app.update();
+        }
+        let events = app.world.resource::<Events<CameraCommand>>();
+        commands
+            .iter(events)
+            .filter(|command| matches!(command, CameraCommand::Reset))
+            .count()
+    };
+
+    assert_eq!(resets(&mut app), 0);
+    app.world
+        .get_mut::<CameraRig>(rig)
+        .unwrap()
+        .disable_input_during_animation = false;
+    assert_eq!(resets(&mut app), 1);
+}
+
+#[test]
+fn smooth_step_eases_in_and_out_of_a_move() {
+    // How far the rig moves each update heading to x = 100
+    fn steps(smooth_step_mode: SmootherKind) -> Vec<f32> {
+        let mut app = test_app();
+        let rig = CameraRig {
+            smooth_step_mode,
+            ..Default::default()
+        };
+        let (rig, _) = spawn_rig(&mut app, rig, Vec3::new(-75., 75., 0.));
+        step(&mut app);
+        app.world.get_mut::<CameraRigTarget>(rig).unwrap().rig =
+            Some(Transform::from_xyz(100., 0., 0.));
+        let mut x = 0.;
+        (0..60)
+            .map(|_| {
+                step(&mut app);
+                let moved = translation(&app, rig).x - x;
+                x += moved;
+                moved
+            })
+            .collect()
+    }
+
+    // Up to where the last bit snaps shut
+    let linear = steps(SmootherKind::Linear);
+    assert!(linear[..20].windows(2).all(|pair| pair[1] <= pair[0]));
+    let eased = steps(SmootherKind::SmoothStep);
+    // Starts slower, speeds up, then slows to a stop at the same place
+    assert!(eased[0] < linear[0] / 2.);
+    let fastest = (0..eased.len())
+        .max_by(|a, b| eased[*a].total_cmp(&eased[*b]))
+        .unwrap();
+    assert!(fastest > 0 && eased[fastest] > eased[0] * 2.);
+    assert!(eased[fastest..20].windows(2).all(|pair| pair[1] <= pair[0]));
+    assert!((eased.iter().sum::<f32>() - 100.).abs() < 0.001);
+    assert!((linear.iter().sum::<f32>() - 100.).abs() < 0.001);
+}
+
+#[test]
+fn bounds_spring_builds_momentum() {
+    let mut app = test_app();
+    let mut rig = CameraRig {
+        spring_return_to_bounds: true,
+        ..Default::default()
+    };
+    rig.set_rect_bounds(Rect::from_center_size(Vec2::ZERO, Vec2::splat(20.)));
+    let (rig, _) = spawn_rig(&mut app, rig, Vec3::new(-75., 75., 0.));
+    *app.world.get_mut::<CameraRigTarget>(rig).unwrap() = CameraRigTarget {
+        rig: Some(Transform::from_xyz(30., 0., 0.)),
+        snap_next_update: true,
+        ..Default::default()
+    };
+    let mut x = vec![];
+    for _ in 0..600 {
+        step(&mut app);
+        x.push(translation(&app, rig).x);
+    }
+
+    // Speeding up from rest rather than jumping most of the way at once
+    assert!(x[0] > 29.);
+    assert!(x[1] - x[2] > x[0] - x[1]);
+    // Coming to rest at the edge without bouncing back in
+    assert!(x.windows(2).all(|pair| pair[1] <= pair[0]));
+    assert!(x.iter().all(|x| *x >= 10.));
+    assert_eq!(x[599], 10.);
+}
+
+#[test]
+fn cursor_grab_follows_last_focus_event() {
+    let mut app = test_app();
+    spawn_rig(
+        &mut app,
+        CameraRig {
+            mouse: MouseConf::default().with_hide_cursor_during_rotate(true),
+            ..Default::default()
+        },
+        Vec3::new(-75., 75., 0.),
+    );
+    let window = app.world.spawn((Window::default(), PrimaryWindow)).id();
+    let cursor_visible = |app: &App| app.world.get::<Window>(window).unwrap().cursor.visible;
+    app.world.send_event(MouseButtonInput {
+        button: MouseButton::Right,
+        state: ButtonState::Pressed,
+    });
+    for _ in 0..3 {
+        step(&mut app);
+    }
+    assert!(!cursor_visible(&app));
+
+    // Focus coming straight back keeps rotating
+    for focused in [false, true] {
+        app.world.send_event(WindowFocused { window, focused });
+    }
+    step(&mut app);
+    assert!(!cursor_visible(&app));
+
+    // Losing focus mid rotation lets the cursor go
+    app.world.send_event(WindowFocused {
+        window,
+        focused: false,
+    });
+    step(&mut app);
+    assert!(cursor_visible(&app));
+}
+
+#[test]
+fn cursor_leaving_after_moving_is_invalid() {
+    let mut app = test_app();
+    app.add_plugin(AssetPlugin::default())
+        .add_asset::<Image>()
+        .add_plugin(CameraProjectionPlugin::<Projection>::default());
+    let (rig, _) = spawn_rig(&mut app, CameraRig::default(), Vec3::new(-75., 75., 0.));
+    let window = app
+        .world
+        .spawn((
+            Window {
+                resolution: WindowResolution::new(800., 600.),
+                ..Default::default()
+            },
+            PrimaryWindow,
+        ))
+        .id();
+    let center = Vec2::new(400., 300.);
+    let valid = |app: &App| app.world.get::<CameraRigCursor>(rig).unwrap().valid;
+    step(&mut app);
+    app.world.send_event(CursorMoved {
+        window,
+        position: center,
+    });
+    step(&mut app);
+    assert!(valid(&app));
+
+    // Moving then leaving within one update ends off the window
+    app.world.send_event(CursorMoved {
+        window,
+        position: center,
+    });
+    app.world.send_event(CursorLeft { window });
+    step(&mut app);
+    assert!(!valid(&app));
+
+    // Leaving and coming back within one update ends on it
+    app.world
+        .get_mut::<Window>(window)
+        .unwrap()
+        .set_cursor_position(Some(center));
+    app.world.send_event(CursorLeft { window });
+    app.world.send_event(CursorMoved {
+        window,
+        position: center,
+    });
+    step(&mut app);
+    assert!(valid(&app));
+}